@@ -0,0 +1,215 @@
+use anyhow::{Context, Result};
+use colored::Colorize;
+use git2::{ApplyLocation, ApplyOptions, Diff, ErrorClass, ErrorCode, Repository};
+
+use crate::git_ops;
+use crate::models::{Comment, InputType, ReviewData};
+use crate::storage;
+use crate::suggestion::comment_patch;
+
+/// Why a suggestion didn't apply
+enum Failure {
+    /// The commented lines no longer match the file
+    Conflict(String),
+    /// Anything else, e.g. a missing file or an unreadable patch
+    Error(anyhow::Error),
+}
+
+impl From<anyhow::Error> for Failure {
+    fn from(error: anyhow::Error) -> Self {
+        Failure::Error(error)
+    }
+}
+
+/// Apply every suggestion in a review JSON file to the working tree,
+/// one comment at a time, and report the outcome of each
+pub fn run(review_path: &str, dry_run: bool) -> Result<()> {
+    let data = storage::load_review_file(review_path)?;
+    let repo = Repository::discover(".")?;
+    apply_review(&repo, &data, dry_run)
+}
+
+fn apply_review(repo: &Repository, data: &ReviewData, dry_run: bool) -> Result<()> {
+    let mut suggestions: Vec<&Comment> = data
+        .comments
+        .iter()
+        .filter(|c| c.suggestion.is_some())
+        .collect();
+
+    if suggestions.is_empty() {
+        println!("{}", "No suggested changes to apply.".dimmed());
+        return Ok(());
+    }
+
+    // Bottom-up within each file, so applying one suggestion doesn't shift the next
+    suggestions.sort_by(|a, b| a.file.cmp(&b.file).then(b.line.cmp(&a.line)));
+
+    let mut conflicts = 0;
+    let mut errors = 0;
+    for comment in &suggestions {
+        let location = format!(
+            "{}:{}",
+            comment.file.as_deref().unwrap_or_default(),
+            comment.line.unwrap_or_default()
+        );
+
+        match apply_comment(repo, &data.input_type, comment, dry_run) {
+            Ok(()) if dry_run => println!("{} {} {}", "✓".green(), location, "applies cleanly".dimmed()),
+            Ok(()) => println!("{} {} {}", "✓".green(), location, "applied".dimmed()),
+            Err(Failure::Conflict(e)) => {
+                conflicts += 1;
+                println!("{} {} {}", "✗".red(), location, format!("conflict: {}", e).red());
+            }
+            Err(Failure::Error(e)) => {
+                errors += 1;
+                println!("{} {} {}", "✗".red(), location, format!("error: {:#}", e).red());
+            }
+        }
+    }
+
+    let failed = conflicts + errors;
+    println!();
+    println!(
+        "{} {} of {} suggestions {}",
+        "Summary:".bold(),
+        (suggestions.len() - failed).to_string().cyan(),
+        suggestions.len(),
+        if dry_run { "would apply" } else { "applied" }
+    );
+
+    if failed > 0 {
+        let mut message = format!("{} suggestion(s) did not apply", failed);
+        if conflicts > 0 {
+            message.push_str(&format!(
+                "; {} conflicted with changes made since the review",
+                conflicts
+            ));
+        }
+        return Err(anyhow::anyhow!(message));
+    }
+    Ok(())
+}
+
+/// Apply (or check) a single comment's suggestion
+fn apply_comment(
+    repo: &Repository,
+    input: &InputType,
+    comment: &Comment,
+    dry_run: bool,
+) -> Result<(), Failure> {
+    let file = comment.file.as_deref().context("Suggestion has no file")?;
+    let path = git_ops::repo_path(repo, input, file)?;
+    let patch = comment_patch(comment, &path).context("Suggestion has no line")?;
+    let diff = Diff::from_buffer(patch.as_bytes())
+        .map_err(|e| anyhow::anyhow!("Invalid patch: {}", e.message()))?;
+
+    let mut opts = ApplyOptions::new();
+    opts.check(dry_run);
+    // libgit2 also reports a file it can't read as ApplyFail, but with an OS error class
+    repo.apply(&diff, ApplyLocation::WorkDir, Some(&mut opts))
+        .map_err(|e| match (e.code(), e.class()) {
+            (ErrorCode::ApplyFail, ErrorClass::Patch) => Failure::Conflict(e.message().to_string()),
+            _ => Failure::Error(anyhow::anyhow!(e.message().to_string())),
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::path::PathBuf;
+
+    /// A repository in a temp dir, removed when dropped
+    struct TempRepo {
+        dir: PathBuf,
+        repo: Repository,
+    }
+
+    impl TempRepo {
+        /// A fresh repository holding `path` with the given content
+        fn with_file(path: &str, content: &[u8]) -> Self {
+            // Canonical, as the repository's workdir is (/tmp is a symlink on macOS)
+            let dir = std::env::temp_dir()
+                .canonicalize()
+                .unwrap()
+                .join(format!("hrevu-apply-{}", uuid::Uuid::new_v4().simple()));
+            let repo = Repository::init(&dir).unwrap();
+            let file = dir.join(path);
+            fs::create_dir_all(file.parent().unwrap()).unwrap();
+            fs::write(file, content).unwrap();
+            Self { dir, repo }
+        }
+
+        fn read(&self, path: &str) -> Vec<u8> {
+            fs::read(self.dir.join(path)).unwrap()
+        }
+    }
+
+    impl Drop for TempRepo {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.dir);
+        }
+    }
+
+    fn commit_input() -> InputType {
+        InputType::CommitDiff { commit: "HEAD".to_string() }
+    }
+
+    #[test]
+    fn applies_suggestion_to_crlf_file() {
+        let temp = TempRepo::with_file("f.txt", b"one\r\ntwo  \r\nthree\r\n");
+        let comment = Comment::suggesting("f.txt", 2, 2, &["two  \r\n"], "TWO");
+
+        assert!(apply_comment(&temp.repo, &commit_input(), &comment, true).is_ok());
+        assert_eq!(temp.read("f.txt"), b"one\r\ntwo  \r\nthree\r\n");
+
+        assert!(apply_comment(&temp.repo, &commit_input(), &comment, false).is_ok());
+        assert_eq!(temp.read("f.txt"), b"one\r\nTWO\r\nthree\r\n");
+    }
+
+    #[test]
+    fn reports_conflict_when_lines_changed() {
+        let temp = TempRepo::with_file("f.txt", b"a\nchanged\nc\n");
+        let comment = Comment::suggesting("f.txt", 2, 2, &["b\n"], "B");
+
+        let result = apply_comment(&temp.repo, &commit_input(), &comment, false);
+        assert!(matches!(result, Err(Failure::Conflict(_))));
+        assert_eq!(temp.read("f.txt"), b"a\nchanged\nc\n");
+    }
+
+    #[test]
+    fn reports_missing_file_as_error() {
+        let temp = TempRepo::with_file("f.txt", b"a\n");
+        let comment = Comment::suggesting("gone.txt", 1, 1, &["a\n"], "A");
+
+        let result = apply_comment(&temp.repo, &commit_input(), &comment, false);
+        assert!(matches!(result, Err(Failure::Error(_))));
+    }
+
+    #[test]
+    fn applies_file_review_from_subdirectory() {
+        let temp = TempRepo::with_file("sub/f.txt", b"a\nb\nc\n");
+        let sub = temp.dir.join("sub");
+
+        // `hrevu f.txt` run in sub/ names the file relative to sub/
+        let relative = review_of("f.txt", &["b\n"], "B");
+        let previous = std::env::current_dir().unwrap();
+        std::env::set_current_dir(&sub).unwrap();
+        let result = apply_review(&temp.repo, &relative, false);
+        std::env::set_current_dir(previous).unwrap();
+        assert!(result.is_ok(), "{:?}", result);
+        assert_eq!(temp.read("sub/f.txt"), b"a\nB\nc\n");
+
+        // `hrevu /abs/path/sub/f.txt` names it absolutely
+        let absolute = review_of(&sub.join("f.txt").to_string_lossy(), &["B\n"], "b");
+        assert!(apply_review(&temp.repo, &absolute, false).is_ok());
+        assert_eq!(temp.read("sub/f.txt"), b"a\nb\nc\n");
+    }
+
+    /// A file review of `path` with one suggestion on line 2
+    fn review_of(path: &str, original: &[&str], replacement: &str) -> ReviewData {
+        let mut data = ReviewData::new(InputType::FileContent { path: path.to_string() });
+        data.comments = vec![Comment::suggesting(path, 2, 2, original, replacement)];
+        data
+    }
+}
@@ -1,20 +1,183 @@
-use clap::Parser;
+use clap::{Parser, Subcommand, ValueEnum};
+use std::path::PathBuf;
+
+use crate::models::WorkingTreeMode;
 
 #[derive(Parser, Debug)]
 #[command(name = "hrevu")]
 #[command(author = "hrevu")]
 #[command(version = "0.1.5")]
 #[command(about = "Human review CLI tool for AI", long_about = None)]
+#[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
 pub struct Args {
-    /// Input: commit hash, file path, or "diff"
-    #[arg(value_name = "INPUT")]
-    pub input: String,
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    /// Input: commit hash, commit range (A..B or A...B), file path, "diff", or "branch"
+    #[arg(value_name = "INPUT", required = true)]
+    pub input: Option<String>,
+
+    /// Base branch for "branch" (default: upstream, main or master)
+    #[arg(value_name = "BASE")]
+    pub base: Option<String>,
+
+    /// Include uncommitted changes in "branch" review
+    #[arg(long, default_value = "false")]
+    pub uncommitted: bool,
 
     /// Port for web server (default: random available port)
     #[arg(short, long, default_value = "0")]
     pub port: u16,
 
-    /// Output results in JSON format
+    /// Only review staged changes with "diff"
+    #[arg(long, default_value = "false", conflicts_with = "unstaged")]
+    pub staged: bool,
+
+    /// Only review unstaged changes with "diff"
+    #[arg(long, default_value = "false")]
+    pub unstaged: bool,
+
+    /// Leave untracked files out of "diff" and "branch --uncommitted"
     #[arg(long, default_value = "false")]
+    pub no_untracked: bool,
+
+    /// Don't print progress messages to stderr, only warnings and the review
+    #[arg(short, long, global = true, default_value = "false")]
+    pub quiet: bool,
+
+    #[command(flatten)]
+    pub output: OutputArgs,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Apply suggested changes from a review's JSON output to the working tree
+    Apply {
+        /// Review JSON written by `hrevu --json`
+        #[arg(value_name = "REVIEW_JSON")]
+        review: String,
+
+        /// Only check whether the suggestions apply, without changing files
+        #[arg(long, default_value = "false")]
+        dry_run: bool,
+    },
+    /// Resume an unfinished review with its comments
+    Resume {
+        /// Review ID shown when the review started
+        #[arg(value_name = "ID")]
+        id: String,
+
+        /// Port for web server (default: random available port)
+        #[arg(short, long, default_value = "0")]
+        port: u16,
+
+        #[command(flatten)]
+        output: OutputArgs,
+    },
+    /// Review the current state of a past review's target, carrying over its comments
+    Rereview {
+        /// Review ID from `hrevu list`
+        #[arg(value_name = "ID")]
+        id: String,
+
+        /// JSON file with the agent's responses: [{"id", "resolution", "reply"}]
+        #[arg(long, value_name = "FILE")]
+        replies: Option<String>,
+
+        /// Port for web server (default: random available port)
+        #[arg(short, long, default_value = "0")]
+        port: u16,
+
+        #[command(flatten)]
+        output: OutputArgs,
+    },
+    /// Re-review from a review's JSON output, with a "changes since last review" view
+    Since {
+        /// Review JSON written by `hrevu --json`
+        #[arg(value_name = "REVIEW_JSON")]
+        review: String,
+
+        /// JSON file with the agent's responses: [{"id", "resolution", "reply"}]
+        #[arg(long, value_name = "FILE")]
+        replies: Option<String>,
+
+        /// Port for web server (default: random available port)
+        #[arg(short, long, default_value = "0")]
+        port: u16,
+
+        #[command(flatten)]
+        output: OutputArgs,
+    },
+    /// List past reviews, newest first
+    List,
+    /// Print a past review again
+    Show {
+        /// Review ID from `hrevu list`
+        #[arg(value_name = "ID")]
+        id: String,
+
+        #[command(flatten)]
+        output: OutputArgs,
+    },
+    /// Print the JSON Schema of the JSON output
+    Schema,
+}
+
+/// How the finished review is printed
+#[derive(clap::Args, Debug)]
+pub struct OutputArgs {
+    /// Output format
+    #[arg(long, value_enum, default_value = "text")]
+    pub format: OutputFormat,
+
+    /// Output results in JSON format (same as --format json)
+    #[arg(long, default_value = "false", conflicts_with = "format")]
     pub json: bool,
+
+    /// Write the review to a file instead of stdout
+    #[arg(short, long = "output", value_name = "FILE")]
+    pub output_file: Option<PathBuf>,
+}
+
+impl OutputArgs {
+    /// The selected format, with --json taking effect as --format json
+    pub fn format(&self) -> OutputFormat {
+        if self.json {
+            OutputFormat::Json
+        } else {
+            self.format
+        }
+    }
+}
+
+/// Output formats for a finished review
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// Colored terminal summary
+    Text,
+    /// The review data as JSON
+    Json,
+    /// Markdown document for PR descriptions and issues
+    Markdown,
+    /// SARIF 2.1.0 log for code scanning viewers
+    Sarif,
+    /// `file:line:col: kind: text` lines for an editor's quickfix list
+    Quickfix,
+    /// Checkstyle XML report
+    Checkstyle,
+    /// reviewdog diagnostic format (rdjson)
+    Rdjson,
+}
+
+impl Args {
+    /// Working tree mode selected by --staged / --unstaged
+    pub fn working_tree_mode(&self) -> WorkingTreeMode {
+        if self.staged {
+            WorkingTreeMode::Staged
+        } else if self.unstaged {
+            WorkingTreeMode::Unstaged
+        } else {
+            WorkingTreeMode::Combined
+        }
+    }
 }
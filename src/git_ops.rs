@@ -4,26 +4,59 @@ use std::path::Path;
 use std::fs;
 use std::cell::RefCell;
 
-use crate::models::{InputType, FileData, LineData};
+use crate::models::{InputType, FileData, Hunk, LineData, WorkingTreeMode};
 
 /// Default number of context lines for diff
 const DIFF_CONTEXT_LINES: u32 = 3;
 
-/// Extract file lines from a FileData vector
-pub fn extract_file_lines(files: Vec<FileData>) -> std::collections::HashMap<String, Vec<String>> {
-    files
-        .into_iter()
-        .map(|file| {
-            let lines: Vec<String> = file.lines.iter().map(|l| l.content.clone()).collect();
-            (file.path, lines)
-        })
-        .collect()
+/// Minimum similarity (percent) for a delete/add pair to count as a rename or copy
+const RENAME_THRESHOLD: u16 = 50;
+
+/// Fallback base branches for `branch` mode when there is no upstream
+const DEFAULT_BASE_BRANCHES: &[&str] = &["main", "master", "origin/main", "origin/master"];
+
+/// Options that refine how the input is interpreted
+#[derive(Debug, Default)]
+pub struct InputOptions<'a> {
+    /// Base branch for `branch` mode
+    pub base: Option<&'a str>,
+    /// Include uncommitted changes in `branch` mode
+    pub include_uncommitted: bool,
+    /// Which changes to show in `diff` mode
+    pub working_tree_mode: WorkingTreeMode,
+    /// Show untracked files in `diff` mode and `branch` mode with uncommitted changes
+    pub include_untracked: bool,
 }
 
 /// Parse user input into InputType
-pub fn parse_input(input: &str) -> Result<InputType> {
+pub fn parse_input(input: &str, options: &InputOptions) -> Result<InputType> {
+    if input == "branch" {
+        return parse_branch_input(options);
+    }
+
+    if options.base.is_some() {
+        return Err(anyhow::anyhow!("A base branch can only be given with 'branch'"));
+    }
+
+    if options.include_uncommitted {
+        return Err(anyhow::anyhow!("--uncommitted can only be given with 'branch'"));
+    }
+
     if input == "diff" {
-        return Ok(InputType::WorkingTreeDiff);
+        return Ok(InputType::WorkingTreeDiff {
+            mode: options.working_tree_mode,
+            include_untracked: options.include_untracked,
+        });
+    }
+
+    if options.working_tree_mode != WorkingTreeMode::Combined {
+        return Err(anyhow::anyhow!("--staged and --unstaged can only be given with 'diff'"));
+    }
+
+    if !options.include_untracked {
+        return Err(anyhow::anyhow!(
+            "--no-untracked can only be given with 'diff' or 'branch --uncommitted'"
+        ));
     }
 
     if Path::new(input).exists() {
@@ -33,6 +66,18 @@ pub fn parse_input(input: &str) -> Result<InputType> {
     }
 
     if let Ok(repo) = Repository::discover(".") {
+        if let Some((from, to, symmetric)) = split_commit_range(input) {
+            if let Ok(spec) = repo.revparse(input) {
+                if spec.mode().is_range() {
+                    return Ok(InputType::CommitRange {
+                        from,
+                        to,
+                        symmetric,
+                    });
+                }
+            }
+        }
+
         if repo.revparse_single(input).is_ok() {
             return Ok(InputType::CommitDiff {
                 commit: input.to_string(),
@@ -41,11 +86,108 @@ pub fn parse_input(input: &str) -> Result<InputType> {
     }
 
     Err(anyhow::anyhow!(
-        "Unable to parse input: {}. Please provide: commit hash, file path, or 'diff'",
+        "Unable to parse input: {}. Please provide: commit hash, commit range, file path, or 'diff'",
         input
     ))
 }
 
+/// Resolve `branch` mode: find the merge base between HEAD and the base branch
+fn parse_branch_input(options: &InputOptions) -> Result<InputType> {
+    if options.working_tree_mode != WorkingTreeMode::Combined {
+        return Err(anyhow::anyhow!("--staged and --unstaged can only be given with 'diff'"));
+    }
+    if !options.include_untracked && !options.include_uncommitted {
+        return Err(anyhow::anyhow!("--no-untracked needs --uncommitted with 'branch'"));
+    }
+
+    let repo = Repository::discover(".")?;
+    let base = match options.base {
+        Some(base) => base.to_string(),
+        None => default_base_branch(&repo)?,
+    };
+
+    let head = repo.head()?.peel_to_commit()?;
+    let base_commit = repo
+        .revparse_single(&base)
+        .and_then(|obj| obj.peel_to_commit())
+        .map_err(|_| anyhow::anyhow!("Unable to resolve base branch: {}", base))?;
+
+    let merge_base = repo
+        .merge_base(head.id(), base_commit.id())
+        .map_err(|_| anyhow::anyhow!("No merge base between HEAD and {}", base))?;
+
+    Ok(InputType::BranchDiff {
+        base,
+        merge_base: merge_base.to_string(),
+        include_uncommitted: options.include_uncommitted,
+        include_untracked: options.include_uncommitted && options.include_untracked,
+    })
+}
+
+/// Pick the default base branch: the upstream of HEAD, then main or master
+fn default_base_branch(repo: &Repository) -> Result<String> {
+    let upstream = repo
+        .head()
+        .ok()
+        .filter(|head| head.is_branch())
+        .and_then(|head| git2::Branch::wrap(head).upstream().ok())
+        .and_then(|upstream| upstream.name().ok().flatten().map(|n| n.to_string()));
+
+    if let Some(upstream) = upstream {
+        return Ok(upstream);
+    }
+
+    DEFAULT_BASE_BRANCHES
+        .iter()
+        .find(|name| repo.revparse_single(name).is_ok())
+        .map(|name| name.to_string())
+        .ok_or_else(|| {
+            anyhow::anyhow!("No upstream, main or master branch found. Please provide a base branch")
+        })
+}
+
+/// Split a commit range ("A..B" or "A...B") into its endpoints.
+/// An omitted endpoint defaults to HEAD, as in git.
+fn split_commit_range(input: &str) -> Option<(String, String, bool)> {
+    let (from, to, symmetric) = if let Some((from, to)) = input.split_once("...") {
+        (from, to, true)
+    } else {
+        let (from, to) = input.split_once("..")?;
+        (from, to, false)
+    };
+
+    let or_head = |rev: &str| {
+        if rev.is_empty() {
+            "HEAD".to_string()
+        } else {
+            rev.to_string()
+        }
+    };
+
+    Some((or_head(from), or_head(to), symmetric))
+}
+
+/// Load the files to review for an input
+pub fn load_files(input: &InputType) -> Result<Vec<FileData>> {
+    match input {
+        InputType::CommitDiff { commit } => get_commit_diff(commit),
+        InputType::CommitRange { from, to, symmetric } => {
+            get_commit_range_diff(from, to, *symmetric)
+        }
+        InputType::BranchDiff {
+            merge_base,
+            include_uncommitted,
+            include_untracked,
+            ..
+        } => get_branch_diff(merge_base, *include_uncommitted, *include_untracked),
+        InputType::FileContent { path } => get_file_content(path),
+        InputType::WorkingTreeDiff {
+            mode,
+            include_untracked,
+        } => get_working_tree_diff(*mode, *include_untracked),
+    }
+}
+
 /// Create diff options with default settings
 fn create_diff_options() -> git2::DiffOptions {
     let mut opts = git2::DiffOptions::new();
@@ -54,17 +196,19 @@ fn create_diff_options() -> git2::DiffOptions {
     opts
 }
 
-/// Get working tree diff (including staged and unstaged changes)
-pub fn get_working_tree_diff() -> Result<Vec<FileData>> {
+/// Show untracked (non-ignored) files, with their content, as added files
+fn include_untracked_files(opts: &mut git2::DiffOptions) {
+    opts.include_untracked(true);
+    opts.recurse_untracked_dirs(true);
+    opts.show_untracked_content(true);
+}
+
+/// Get working tree diff for the given mode
+pub fn get_working_tree_diff(mode: WorkingTreeMode, include_untracked: bool) -> Result<Vec<FileData>> {
     let repo = Repository::discover(".")?;
     let head_tree = get_head_tree(&repo)?;
 
-    let result = match head_tree {
-        Some(tree) => get_diff_with_head(&repo, &tree)?,
-        None => get_untracked_files(&repo)?,
-    };
-
-    let mut files: Vec<FileData> = result;
+    let mut files = get_diff_for_mode(&repo, mode, head_tree.as_ref(), include_untracked)?;
     files.sort_by(|a, b| a.path.cmp(&b.path));
     Ok(files)
 }
@@ -80,68 +224,29 @@ fn get_head_tree(repo: &Repository) -> Result<Option<git2::Tree<'_>>> {
     }
 }
 
-/// Get diff when HEAD exists (merge staged and unstaged changes)
-fn get_diff_with_head(repo: &Repository, tree: &git2::Tree) -> Result<Vec<FileData>> {
-    let mut workdir_opts = create_diff_options();
-    workdir_opts.recurse_untracked_dirs(true);
-
-    let mut cached_opts = create_diff_options();
-
-    let cached_diff = repo.diff_tree_to_index(Some(tree), None, Some(&mut cached_opts))?;
-    let workdir_diff = repo.diff_index_to_workdir(None, Some(&mut workdir_opts))?;
-
-    merge_diffs(cached_diff, workdir_diff)
-}
-
-/// Merge two diffs into a single FileData vector
-fn merge_diffs(cached_diff: Diff, workdir_diff: Diff) -> Result<Vec<FileData>> {
-    let mut merged_files: std::collections::HashMap<String, FileData> = std::collections::HashMap::new();
-
-    for file in diff_to_file_data(&cached_diff)? {
-        merged_files.insert(file.path.clone(), file);
+/// Diff HEAD against the index, the index against the workdir,
+/// or HEAD directly against the workdir with the index applied.
+/// Without a HEAD, everything in the index shows up as added.
+fn get_diff_for_mode(
+    repo: &Repository,
+    mode: WorkingTreeMode,
+    head_tree: Option<&git2::Tree>,
+    include_untracked: bool,
+) -> Result<Vec<FileData>> {
+    let mut opts = create_diff_options();
+    if include_untracked {
+        include_untracked_files(&mut opts);
     }
 
-    for file in diff_to_file_data(&workdir_diff)? {
-        if let Some(existing) = merged_files.get_mut(&file.path) {
-            existing.lines.extend(file.lines);
-        } else {
-            merged_files.insert(file.path.clone(), file);
+    let mut diff = match mode {
+        WorkingTreeMode::Staged => repo.diff_tree_to_index(head_tree, None, Some(&mut opts))?,
+        WorkingTreeMode::Unstaged => repo.diff_index_to_workdir(None, Some(&mut opts))?,
+        WorkingTreeMode::Combined => {
+            repo.diff_tree_to_workdir_with_index(head_tree, Some(&mut opts))?
         }
-    }
-
-    Ok(merged_files.into_values().collect())
-}
-
-/// Get untracked files when HEAD doesn't exist
-fn get_untracked_files(repo: &Repository) -> Result<Vec<FileData>> {
-    let mut files = Vec::new();
-    let statuses = repo.statuses(None)?;
-
-    for entry in statuses.iter() {
-        let status = entry.status();
-
-        let is_relevant = status.is_index_new()
-            || status.is_wt_new()
-            || status.is_index_modified()
-            || status.is_wt_modified();
-
-        let is_deleted = status.is_index_deleted() || status.is_wt_deleted();
-
-        if is_relevant && !is_deleted {
-            if let Some(path) = entry.path() {
-                if let Ok(content) = fs::read_to_string(path) {
-                    let lines = enumerate_file_lines(&content, Some("added"));
-                    files.push(FileData {
-                        path: path.to_string(),
-                        status: "added".to_string(),
-                        lines,
-                    });
-                }
-            }
-        }
-    }
+    };
 
-    Ok(files)
+    diff_to_file_data(&mut diff)
 }
 
 /// Get commit diff
@@ -160,103 +265,284 @@ pub fn get_commit_diff(commit_hash: &str) -> Result<Vec<FileData>> {
     let commit_tree = commit.tree()?;
     let mut diff_opts = create_diff_options();
 
-    let diff = repo.diff_tree_to_tree(parent_tree.as_ref(), Some(&commit_tree), Some(&mut diff_opts))?;
+    let mut diff = repo.diff_tree_to_tree(parent_tree.as_ref(), Some(&commit_tree), Some(&mut diff_opts))?;
+
+    diff_to_file_data(&mut diff)
+}
+
+/// Get the combined diff of a commit range.
+/// "A..B" diffs A against B; "A...B" diffs the merge base of A and B against B.
+pub fn get_commit_range_diff(from: &str, to: &str, symmetric: bool) -> Result<Vec<FileData>> {
+    let repo = Repository::discover(".")?;
+    let from_commit = repo.revparse_single(from)?.peel_to_commit()?;
+    let to_commit = repo.revparse_single(to)?.peel_to_commit()?;
+
+    let base_commit = if symmetric {
+        let base = repo.merge_base(from_commit.id(), to_commit.id())?;
+        repo.find_commit(base)?
+    } else {
+        from_commit
+    };
+
+    let base_tree = base_commit.tree()?;
+    let to_tree = to_commit.tree()?;
+    let mut diff_opts = create_diff_options();
+
+    let mut diff = repo.diff_tree_to_tree(Some(&base_tree), Some(&to_tree), Some(&mut diff_opts))?;
 
-    diff_to_file_data(&diff)
+    let mut files = diff_to_file_data(&mut diff)?;
+    files.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(files)
+}
+
+/// Get everything the branch introduces since its merge base,
+/// optionally including uncommitted changes and untracked files
+pub fn get_branch_diff(
+    merge_base: &str,
+    include_uncommitted: bool,
+    include_untracked: bool,
+) -> Result<Vec<FileData>> {
+    let repo = Repository::discover(".")?;
+    let base_tree = repo.find_commit(git2::Oid::from_str(merge_base)?)?.tree()?;
+    let mut diff_opts = create_diff_options();
+
+    let mut diff = if include_uncommitted {
+        if include_untracked {
+            include_untracked_files(&mut diff_opts);
+        }
+        repo.diff_tree_to_workdir_with_index(Some(&base_tree), Some(&mut diff_opts))?
+    } else {
+        let head_tree = repo.head()?.peel_to_commit()?.tree()?;
+        repo.diff_tree_to_tree(Some(&base_tree), Some(&head_tree), Some(&mut diff_opts))?
+    };
+
+    let mut files = diff_to_file_data(&mut diff)?;
+    files.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(files)
 }
 
 /// Get file content
 pub fn get_file_content(path: &str) -> Result<Vec<FileData>> {
-    let content = fs::read_to_string(path)?;
-    let lines = enumerate_file_lines(&content, None);
+    let bytes = fs::read(path)?;
+    let new_oid = git2::Oid::hash_object(git2::ObjectType::Blob, &bytes)?;
+
+    let (binary, hunks) = match String::from_utf8(bytes) {
+        Ok(content) if !content.contains('\0') => (false, vec![whole_file_hunk(&content)]),
+        _ => (true, Vec::new()),
+    };
 
     Ok(vec![FileData {
         path: path.to_string(),
         status: "view".to_string(),
-        lines,
+        old_path: None,
+        similarity: None,
+        binary,
+        old_size: None,
+        new_size: Some(fs::metadata(path)?.len()),
+        old_oid: None,
+        new_oid: Some(new_oid.to_string()),
+        hunks,
     }])
 }
 
-/// Enumerate file content into LineData
-fn enumerate_file_lines(content: &str, line_type: Option<&str>) -> Vec<LineData> {
-    content
+/// Content of a file as reviewed, byte for byte: the file itself for file reviews,
+/// otherwise the new blob, or the working tree file if that blob isn't in the object database
+pub fn reviewed_content(input: &InputType, file: &FileData) -> Result<Vec<u8>> {
+    if let InputType::FileContent { path } = input {
+        return Ok(fs::read(path)?);
+    }
+
+    let repo = Repository::discover(".")?;
+    if let Some(oid) = file.new_oid.as_deref().map(git2::Oid::from_str).transpose()? {
+        if let Ok(blob) = repo.find_blob(oid) {
+            return Ok(blob.content().to_vec());
+        }
+    }
+    let workdir = repo
+        .workdir()
+        .ok_or_else(|| anyhow::anyhow!("Repository has no working directory"))?;
+    Ok(fs::read(workdir.join(&file.path))?)
+}
+
+/// Wrap plain file content in a single header-less hunk
+fn whole_file_hunk(content: &str) -> Hunk {
+    let lines: Vec<LineData> = content
         .lines()
         .enumerate()
         .map(|(i, line)| LineData {
-            number: (i + 1) as u32,
+            old_number: None,
+            new_number: Some((i + 1) as u32),
             content: line.to_string(),
-            type_: line_type.map(|t| t.to_string()),
+            type_: None,
         })
-        .collect()
+        .collect();
+
+    Hunk {
+        header: String::new(),
+        old_start: 0,
+        old_lines: 0,
+        new_start: 1,
+        new_lines: lines.len() as u32,
+        lines,
+    }
+}
+
+/// Run rename and copy detection on a diff
+fn find_renames(diff: &mut Diff) -> Result<()> {
+    let mut find_opts = git2::DiffFindOptions::new();
+    find_opts
+        .renames(true)
+        .copies(true)
+        .for_untracked(true)
+        .rename_threshold(RENAME_THRESHOLD)
+        .copy_threshold(RENAME_THRESHOLD);
+    diff.find_similar(Some(&mut find_opts))?;
+    Ok(())
+}
+
+/// Collect rename/copy similarity scores keyed by new path.
+/// git2 doesn't expose the score on DiffDelta, but the raw format prints it
+/// as part of the status (e.g. "R087").
+fn collect_similarity(diff: &Diff) -> Result<std::collections::HashMap<String, u16>> {
+    let mut scores = std::collections::HashMap::new();
+
+    diff.print(git2::DiffFormat::Raw, |delta, _hunk, line| {
+        if !matches!(delta.status(), Delta::Renamed | Delta::Copied) {
+            return true;
+        }
+
+        let score = std::str::from_utf8(line.content())
+            .ok()
+            .and_then(|raw| raw.split('\t').next())
+            .and_then(|meta| meta.split_whitespace().last())
+            .and_then(|status| status.get(1..))
+            .and_then(|score| score.parse::<u16>().ok());
+
+        if let Some(score) = score {
+            scores.insert(delta_path(&delta), score);
+        }
+        true
+    })?;
+
+    Ok(scores)
+}
+
+/// Path of a delta: the new path, or the old one for deletions
+fn delta_path(delta: &git2::DiffDelta) -> String {
+    delta
+        .new_file()
+        .path()
+        .or_else(|| delta.old_file().path())
+        .map(|p| p.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
+
+/// Size and blob OID of one side of a delta, if that side exists
+fn diff_file_info(file: &git2::DiffFile) -> (Option<u64>, Option<String>) {
+    if !file.exists() {
+        return (None, None);
+    }
+    let oid = Some(file.id()).filter(|id| !id.is_zero()).map(|id| id.to_string());
+    (Some(file.size()), oid)
 }
 
-/// Convert git2 Diff to FileData
-fn diff_to_file_data(diff: &Diff) -> Result<Vec<FileData>> {
+/// Convert git2 Diff to FileData, detecting renames and copies first
+fn diff_to_file_data(diff: &mut Diff) -> Result<Vec<FileData>> {
+    find_renames(diff)?;
+    let similarity = collect_similarity(diff)?;
     let files_map: RefCell<std::collections::HashMap<String, FileData>> = RefCell::new(std::collections::HashMap::new());
 
     diff.foreach(
         &mut |delta, _progress| {
-            let path = delta
-                .new_file()
-                .path()
-                .and_then(|p| p.to_str())
-                .unwrap_or("binary")
-                .to_string();
+            let path = delta_path(&delta);
 
             let status = match delta.status() {
                 Delta::Added => "added",
                 Delta::Deleted => "deleted",
-                Delta::Modified | Delta::Renamed | Delta::Copied => "modified",
+                Delta::Untracked => "untracked",
+                Delta::Renamed => "renamed",
+                Delta::Copied => "copied",
                 _ => "modified",
             };
 
+            let old_path = match delta.status() {
+                Delta::Renamed | Delta::Copied => delta
+                    .old_file()
+                    .path()
+                    .map(|p| p.to_string_lossy().into_owned()),
+                _ => None,
+            };
+
+            let binary = delta.flags().is_binary()
+                || delta.old_file().is_binary()
+                || delta.new_file().is_binary();
+            let (old_size, old_oid) = diff_file_info(&delta.old_file());
+            let (new_size, new_oid) = diff_file_info(&delta.new_file());
+
             files_map.borrow_mut().insert(
                 path.clone(),
                 FileData {
                     path: path.clone(),
                     status: status.to_string(),
-                    lines: Vec::new(),
+                    old_path,
+                    similarity: similarity.get(&path).copied(),
+                    binary,
+                    old_size,
+                    new_size,
+                    old_oid,
+                    new_oid,
+                    hunks: Vec::new(),
                 },
             );
             true
         },
-        None,
-        Some(&mut |_delta, _hunk| true),
+        Some(&mut |delta, _binary| {
+            if let Some(file) = files_map.borrow_mut().get_mut(&delta_path(&delta)) {
+                file.binary = true;
+            }
+            true
+        }),
+        Some(&mut |delta, hunk| {
+            if let Some(file) = files_map.borrow_mut().get_mut(&delta_path(&delta)) {
+                file.hunks.push(Hunk {
+                    header: String::from_utf8_lossy(hunk.header()).trim_end().to_string(),
+                    old_start: hunk.old_start(),
+                    old_lines: hunk.old_lines(),
+                    new_start: hunk.new_start(),
+                    new_lines: hunk.new_lines(),
+                    lines: Vec::new(),
+                });
+            }
+            true
+        }),
         Some(&mut |delta, _hunk, line| {
-            let path = delta
-                .new_file()
-                .path()
-                .and_then(|p| p.to_str())
-                .unwrap_or("binary")
-                .to_string();
+            let path = delta_path(&delta);
 
             if line.content().is_empty() {
                 return true;
             }
 
+            let line_type = match line.origin() {
+                '+' => Some("added"),
+                '-' => Some("removed"),
+                ' ' => None,
+                // "\ No newline at end of file" markers aren't real lines
+                _ => return true,
+            };
+
             let content = std::str::from_utf8(line.content())
                 .unwrap_or("")
                 .trim_end()
                 .to_string();
 
-            let line_type = match line.origin() {
-                '+' | '>' => Some("added"),
-                '-' | '<' => Some("removed"),
-                _ => None,
-            };
-
-            let is_removed = line_type == Some("removed");
-            let line_num = line.new_lineno().unwrap_or_else(|| {
-                if is_removed {
-                    line.old_lineno().unwrap_or(0)
-                } else {
-                    0
-                }
-            });
-
-            if let Some(file) = files_map.borrow_mut().get_mut(&path) {
-                file.lines.push(LineData {
-                    number: line_num,
+            if let Some(hunk) = files_map
+                .borrow_mut()
+                .get_mut(&path)
+                .and_then(|file| file.hunks.last_mut())
+            {
+                hunk.lines.push(LineData {
+                    old_number: line.old_lineno(),
+                    new_number: line.new_lineno(),
                     content,
                     type_: line_type.map(|t| t.to_string()),
                 });
@@ -267,3 +553,148 @@ fn diff_to_file_data(diff: &Diff) -> Result<Vec<FileData>> {
 
     Ok(files_map.into_inner().into_values().collect())
 }
+
+/// Record the reviewed content as a git tree, so later rounds can diff against it.
+/// A ref under refs/hrevu/ keeps the tree from being garbage collected.
+/// Returns None outside a repository, where there is nowhere to keep it.
+pub fn record_snapshot(id: &str, input: &InputType, files: &[FileData]) -> Result<Option<String>> {
+    let Ok(repo) = Repository::discover(".") else {
+        return Ok(None);
+    };
+    let tree = build_snapshot(&repo, input, files)?;
+    repo.reference(
+        &format!("refs/hrevu/{}", id),
+        tree,
+        true,
+        "hrevu: reviewed snapshot",
+    )?;
+    Ok(Some(tree.to_string()))
+}
+
+/// Whether a snapshot tree is still in the repository's object database
+pub fn has_snapshot(tree: &str) -> bool {
+    let Ok(oid) = git2::Oid::from_str(tree) else {
+        return false;
+    };
+    Repository::discover(".").is_ok_and(|repo| repo.find_tree(oid).is_ok())
+}
+
+/// Changes from a previous round's snapshot to the current state of the same target
+pub fn get_interdiff(previous_tree: &str, input: &InputType, files: &[FileData]) -> Result<Vec<FileData>> {
+    let repo = Repository::discover(".")?;
+    let old_tree = repo.find_tree(git2::Oid::from_str(previous_tree)?)?;
+    let new_tree = repo.find_tree(build_snapshot(&repo, input, files)?)?;
+    let mut diff_opts = create_diff_options();
+    // The snapshot holds the whole project, but a file review only covers the file
+    if let InputType::FileContent { path } = input {
+        diff_opts.pathspec(repo_relative_path(&repo, path)?);
+        diff_opts.disable_pathspec_match(true);
+    }
+
+    let mut diff = repo.diff_tree_to_tree(Some(&old_tree), Some(&new_tree), Some(&mut diff_opts))?;
+
+    let mut files = diff_to_file_data(&mut diff)?;
+    files.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(files)
+}
+
+/// Build a tree of the whole project as reviewed: the commit the input ends at
+/// (HEAD for working tree and file reviews) with the reviewed files on top
+fn build_snapshot(repo: &Repository, input: &InputType, files: &[FileData]) -> Result<git2::Oid> {
+    let base_tree = match input {
+        InputType::CommitDiff { commit: to } | InputType::CommitRange { to, .. } => {
+            Some(repo.revparse_single(to)?.peel_to_commit()?.tree()?)
+        }
+        _ => get_head_tree(repo)?,
+    };
+
+    let mut index = git2::Index::new()?;
+    if let Some(tree) = &base_tree {
+        index.read_tree(tree)?;
+    }
+
+    for file in files {
+        let path = match input {
+            InputType::FileContent { path } => repo_relative_path(repo, path)?,
+            _ => file.path.clone(),
+        };
+
+        if file.status == "renamed" {
+            if let Some(old_path) = &file.old_path {
+                index.remove_path(Path::new(old_path))?;
+            }
+        }
+
+        if file.status == "deleted" {
+            index.remove_path(Path::new(&path))?;
+            continue;
+        }
+
+        // Working tree content isn't in the object database until it is written as a blob
+        let oid = match file.new_oid.as_deref().map(git2::Oid::from_str).transpose()? {
+            Some(oid) if repo.find_blob(oid).is_ok() => oid,
+            _ => {
+                let workdir = repo
+                    .workdir()
+                    .ok_or_else(|| anyhow::anyhow!("Repository has no working directory"))?;
+                repo.blob(&fs::read(workdir.join(&path))?)?
+            }
+        };
+
+        let mode = index
+            .get_path(Path::new(&path), 0)
+            .map_or(0o100644, |entry| entry.mode);
+        index.add(&index_entry(&path, oid, mode))?;
+    }
+
+    Ok(index.write_tree_to(repo)?)
+}
+
+/// Path of a reviewed file relative to the repository root, as patches name it.
+/// File reviews name the file as given on the command line, relative to the current
+/// directory or absolute; other reviews already use repository paths.
+pub fn repo_path(repo: &Repository, input: &InputType, path: &str) -> Result<String> {
+    match input {
+        InputType::FileContent { .. } => repo_relative_path(repo, path),
+        _ => Ok(path.to_string()),
+    }
+}
+
+/// Repository path and reviewed content of a file, for the suggestions patch
+pub fn patch_source(input: &InputType, file: &FileData) -> Result<(String, Vec<u8>)> {
+    let repo = Repository::discover(".").map_err(|_| anyhow::anyhow!("not in a git repository"))?;
+    Ok((repo_path(&repo, input, &file.path)?, reviewed_content(input, file)?))
+}
+
+/// Path of a file relative to the repository's working directory
+fn repo_relative_path(repo: &Repository, path: &str) -> Result<String> {
+    let workdir = repo
+        .workdir()
+        .ok_or_else(|| anyhow::anyhow!("Repository has no working directory"))?
+        .canonicalize()?;
+    let absolute = Path::new(path)
+        .canonicalize()
+        .map_err(|e| anyhow::anyhow!("Unable to find {}: {}", path, e))?;
+    let relative = absolute
+        .strip_prefix(&workdir)
+        .map_err(|_| anyhow::anyhow!("{} is outside the repository", path))?;
+    Ok(relative.to_string_lossy().replace('\\', "/"))
+}
+
+/// Minimal index entry for a blob; only the path, mode and OID matter for writing a tree
+fn index_entry(path: &str, oid: git2::Oid, mode: u32) -> git2::IndexEntry {
+    git2::IndexEntry {
+        ctime: git2::IndexTime::new(0, 0),
+        mtime: git2::IndexTime::new(0, 0),
+        dev: 0,
+        ino: 0,
+        mode,
+        uid: 0,
+        gid: 0,
+        file_size: 0,
+        id: oid,
+        flags: path.len().min(0xfff) as u16,
+        flags_extended: 0,
+        path: path.as_bytes().to_vec(),
+    }
+}
@@ -0,0 +1,60 @@
+use anyhow::Result;
+use colored::Colorize;
+
+use crate::git_ops;
+use crate::models::{ReviewData, ReviewStatus, Verdict};
+use crate::cli::{OutputArgs, OutputFormat};
+use crate::output::write_review;
+use crate::storage;
+
+/// Print one line per archived review: ID, date, outcome, comment count and target
+pub fn list() -> Result<()> {
+    let reviews = storage::list_reviews()?;
+    if reviews.is_empty() {
+        println!("{}", "No reviews yet.".dimmed());
+        return Ok(());
+    }
+
+    for data in &reviews {
+        println!(
+            "{}  {}  {}  {:>3} comments  {}",
+            data.id.cyan(),
+            data.created_at.format("%Y-%m-%d %H:%M").to_string().dimmed(),
+            outcome(data),
+            data.comments.len(),
+            data.input
+        );
+    }
+    Ok(())
+}
+
+/// Print an archived review as it was printed when it finished
+pub fn show(id: &str, output: &OutputArgs) -> Result<()> {
+    let data = storage::load_review(id)?;
+
+    // Context lines come from the target as it is now, so they can drift
+    // for working tree reviews; the comments themselves are as archived
+    let files = match output.format() {
+        OutputFormat::Text | OutputFormat::Markdown => {
+            git_ops::load_files(&data.input_type).unwrap_or_default()
+        }
+        _ => Vec::new(),
+    };
+    write_review(&data, &files, output)
+}
+
+/// Verdict, or status for reviews finished without one, padded before coloring
+fn outcome(data: &ReviewData) -> colored::ColoredString {
+    let label = match (data.status, data.verdict) {
+        (ReviewStatus::Cancelled, _) => "Cancelled",
+        (_, Some(verdict)) => verdict.label(),
+        (_, None) => "Completed",
+    };
+    let padded = format!("{:<18}", label);
+    match (data.status, data.verdict) {
+        (ReviewStatus::Cancelled, _) => padded.yellow(),
+        (_, Some(Verdict::Approve)) => padded.green(),
+        (_, Some(Verdict::RequestChanges)) => padded.red(),
+        _ => padded.normal(),
+    }
+}
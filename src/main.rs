@@ -1,22 +1,26 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
 use clap::Parser;
 use colored::Colorize;
 use tracing::{info, warn};
-use chrono::Utc;
-use std::collections::HashMap;
-use std::process::Command;
+use std::process::{Command, ExitCode};
 
+mod apply;
 mod cli;
 mod server;
 mod git_ops;
+mod history;
 mod models;
 mod routes;
 mod output;
+mod schema;
 mod static_assets;
+mod storage;
+mod suggestion;
 
 use cli::Args;
-use git_ops::{parse_input, extract_file_lines};
-use output::{print_summary, print_json};
+use git_ops::{parse_input, InputOptions};
+use models::{AgentResponse, CommentAuthor, ReviewData, ReviewStatus, ReviewedFile};
+use cli::OutputArgs;
 
 /// Detect if running under WSL
 fn is_wsl() -> bool {
@@ -46,82 +50,230 @@ fn open_browser(url: &str) -> Result<()> {
 }
 
 #[tokio::main]
-async fn main() -> Result<()> {
+async fn main() -> Result<ExitCode> {
+    let args = Args::parse();
+
+    // Logs go to stderr so stdout holds only the review
+    let default_level = if args.quiet { "warn" } else { "info" };
     tracing_subscriber::fmt()
         .with_env_filter(
             tracing_subscriber::EnvFilter::try_from_default_env()
-                .unwrap_or_else(|_| tracing_subscriber::EnvFilter::new("info")),
+                .unwrap_or_else(|_| tracing_subscriber::EnvFilter::new(default_level)),
         )
+        .with_writer(std::io::stderr)
         .init();
 
-    let args = Args::parse();
-    let input = parse_input(&args.input)?;
+    match &args.command {
+        Some(cli::Command::Apply { review, dry_run }) => {
+            apply::run(review, *dry_run).map(|_| ExitCode::SUCCESS)
+        }
+        Some(cli::Command::Resume { id, port, output }) => {
+            resume_review(id, *port, output, args.quiet).await
+        }
+        Some(cli::Command::Rereview { id, replies, port, output }) => {
+            let previous = storage::load_review(id)?;
+            rereview(previous, replies.as_deref(), *port, output, args.quiet).await
+        }
+        Some(cli::Command::Since { review, replies, port, output }) => {
+            let previous = storage::load_review_file(review)?;
+            match previous.tree.as_deref() {
+                None => anyhow::bail!(
+                    "{} has no snapshot of the reviewed content to compare with",
+                    review
+                ),
+                Some(tree) if !git_ops::has_snapshot(tree) => anyhow::bail!(
+                    "Snapshot {} of {} is not in this repository (was refs/hrevu/{} deleted?)",
+                    tree,
+                    review,
+                    previous.id
+                ),
+                Some(_) => {}
+            }
+            rereview(previous, replies.as_deref(), *port, output, args.quiet).await
+        }
+        Some(cli::Command::List) => history::list().map(|_| ExitCode::SUCCESS),
+        Some(cli::Command::Show { id, output }) => {
+            history::show(id, output).map(|_| ExitCode::SUCCESS)
+        }
+        Some(cli::Command::Schema) => {
+            println!("{}", serde_json::to_string_pretty(&schema::json_schema())?);
+            Ok(ExitCode::SUCCESS)
+        }
+        None => run_review(&args).await,
+    }
+}
+
+/// Start a review of the input given on the command line
+async fn run_review(args: &Args) -> Result<ExitCode> {
+    let input_options = InputOptions {
+        base: args.base.as_deref(),
+        include_uncommitted: args.uncommitted,
+        working_tree_mode: args.working_tree_mode(),
+        include_untracked: !args.no_untracked,
+    };
+    let input = parse_input(args.input.as_deref().unwrap_or_default(), &input_options)?;
     info!("Parsed input: {:?}", input);
 
-    let input_str = input.display_title();
+    serve_review(ReviewData::new(input), args.port, &args.output, args.quiet).await
+}
 
-    let data = crate::models::ReviewData {
-        input_type: input.clone(),
-        input: input_str,
-        comments: Vec::new(),
-        created_at: Utc::now(),
-        status: crate::models::ReviewStatus::InProgress,
-    };
+/// Reopen a saved session on the same target, keeping its comments
+async fn resume_review(id: &str, port: u16, output: &OutputArgs, quiet: bool) -> Result<ExitCode> {
+    let mut data = storage::load_session(id)?;
+    data.status = ReviewStatus::InProgress;
+    data.verdict = None;
+    info!("Resuming review {} with {} comments", data.id, data.comments.len());
+
+    serve_review(data, port, output, quiet).await
+}
+
+/// Start a new round on a past review's target: its comments carry over, with the
+/// agent's responses from the replies file applied. If the past review recorded a
+/// snapshot, the changes since then are offered as a separate view.
+async fn rereview(
+    previous: ReviewData,
+    replies: Option<&str>,
+    port: u16,
+    output: &OutputArgs,
+    quiet: bool,
+) -> Result<ExitCode> {
+    let mut data = ReviewData::new(previous.input_type);
+    data.previous_tree = previous.tree.filter(|tree| {
+        let found = git_ops::has_snapshot(tree);
+        if !found {
+            warn!(
+                "Snapshot {} of review {} is gone; skipping the since-last-review view",
+                tree,
+                previous.id
+            );
+        }
+        found
+    });
+    data.previous_review = Some(previous.id);
+    data.comments = previous.comments;
+
+    if let Some(path) = replies {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Unable to read {}", path))?;
+        let responses: Vec<AgentResponse> = serde_json::from_str(&content)
+            .with_context(|| format!("{} is not a list of comment responses", path))?;
 
-    println!();
-    println!("{}", "▶ Starting hrevu...".bold().cyan());
-    println!("  Target: {}", data.input);
-    println!();
+        for response in responses {
+            let Some(comment) = data.comments.iter_mut().find(|c| c.id == response.id) else {
+                warn!("No comment {} in the previous review", response.id);
+                continue;
+            };
+            if let Some(resolution) = response.resolution {
+                comment.resolution = resolution;
+            }
+            if let Some(text) = response.reply {
+                let reply = comment.reply(CommentAuthor::Agent, text);
+                comment.replies.push(reply);
+            }
+        }
+    }
 
-    let port = server::run(args.port, data).await?;
+    serve_review(data, port, output, quiet).await
+}
+
+/// Start the review server and print the result once the review completes.
+/// The session is saved as comments change, so an interrupted review can be resumed.
+/// The exit code reflects the reviewer's verdict, or that the review was cancelled.
+/// Progress messages go to stderr, so stdout holds only the review.
+async fn serve_review(
+    data: ReviewData,
+    port: u16,
+    output: &OutputArgs,
+    quiet: bool,
+) -> Result<ExitCode> {
+    if let Err(e) = storage::save_session(&data) {
+        warn!("Failed to save review session: {}", e);
+    }
+
+    if !quiet {
+        eprintln!();
+        eprintln!("{}", "▶ Starting hrevu...".bold().cyan());
+        eprintln!("  Target: {}", data.input);
+        eprintln!("  Review: {}", data.id);
+        eprintln!();
+    }
+
+    let state = server::AppState::new(data);
+    let port = server::run(port, state.clone()).await?;
     let url = format!("http://localhost:{}", port);
 
-    println!("  Server: {}", url.dimmed());
-    println!();
+    if !quiet {
+        eprintln!("  Server: {}", url.dimmed());
+        eprintln!();
+    }
 
     if let Err(e) = open_browser(&url) {
         warn!("Failed to open browser: {}", e);
-        println!("  {}", format!("Please open {} in your browser", url).yellow());
-    } else {
-        println!("  {}", "Browser opened automatically".green());
+        // Shown even when quiet: without it the reviewer can't find the review
+        eprintln!("  {}", format!("Please open {} in your browser", url).yellow());
+    } else if !quiet {
+        eprintln!("  {}", "Browser opened automatically".green());
     }
 
-    println!();
-    println!("{}", "Waiting for review to complete...".dimmed());
-    println!("{}", "Press Ctrl+C to cancel".dimmed());
-    println!();
+    if !quiet {
+        eprintln!();
+        eprintln!("{}", "Waiting for review to complete...".dimmed());
+        eprintln!("{}", "Press Ctrl+C to cancel".dimmed());
+        eprintln!();
+    }
 
-    let final_data = server::wait_for_completion().await?;
+    let mut final_data = server::wait_for_completion(&state).await?;
 
-    let file_contents: HashMap<String, Vec<String>> = match &final_data.input_type {
-        crate::models::InputType::FileContent { path } => {
-            if let Ok(content) = std::fs::read_to_string(path) {
-                let lines: Vec<String> = content.lines().map(|s| s.to_string()).collect();
-                HashMap::from([(path.clone(), lines)])
-            } else {
-                HashMap::new()
-            }
-        }
-        crate::models::InputType::CommitDiff { commit } => {
-            git_ops::get_commit_diff(commit)
-                .map(extract_file_lines)
-                .unwrap_or_default()
-        }
-        crate::models::InputType::WorkingTreeDiff => {
-            git_ops::get_working_tree_diff()
-                .map(extract_file_lines)
-                .unwrap_or_default()
+    let files = git_ops::load_files(&final_data.input_type).unwrap_or_default();
+    final_data.files = files.iter().map(ReviewedFile::from).collect();
+    final_data.suggestions_patch = suggestion::render_patch(&final_data.open_comments(), |path| {
+        let file = files
+            .iter()
+            .find(|f| f.path == path)
+            .ok_or_else(|| anyhow::anyhow!("not under review"))?;
+        git_ops::patch_source(&final_data.input_type, file)
+    });
+
+    // A cancelled review is resumed later, and snapshotted once it completes
+    if final_data.status != ReviewStatus::Cancelled {
+        match git_ops::record_snapshot(&final_data.id, &final_data.input_type, &files) {
+            Ok(tree) => final_data.tree = tree,
+            Err(e) => warn!("Failed to record reviewed snapshot: {}", e),
         }
-    };
+    }
 
-    if args.json {
-        print_json(&final_data);
-    } else {
-        print_summary(&final_data, &file_contents);
+    if let Err(e) = storage::archive_review(&final_data) {
+        warn!("Failed to archive review: {}", e);
     }
 
-    println!();
-    println!("{}", "✓ Review complete!".bold().green());
+    output::write_review(&final_data, &files, output)?;
 
-    Ok(())
+    let cancelled = final_data.status == ReviewStatus::Cancelled;
+    if !cancelled {
+        if let Err(e) = storage::remove_session(&final_data.id) {
+            warn!("Failed to remove review session: {}", e);
+        }
+    }
+
+    if !quiet {
+        eprintln!();
+        if let Some(path) = &output.output_file {
+            eprintln!("{}", format!("  Review written to {}", path.display()).dimmed());
+        }
+        if cancelled {
+            eprintln!("{}", "✗ Review cancelled".bold().yellow());
+            eprintln!(
+                "{}",
+                format!("  Resume with: hrevu resume {}", final_data.id).dimmed()
+            );
+        } else {
+            eprintln!("{}", "✓ Review complete!".bold().green());
+            eprintln!(
+                "{}",
+                format!("  Show again with: hrevu show {}", final_data.id).dimmed()
+            );
+        }
+    }
+
+    Ok(ExitCode::from(final_data.exit_code()))
 }
@@ -5,6 +5,9 @@ use uuid::Uuid;
 /// Review data
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ReviewData {
+    /// Short review ID, used to name its session file
+    #[serde(default)]
+    pub id: String,
     /// Input type
     pub input_type: InputType,
     /// Original input
@@ -15,6 +18,120 @@ pub struct ReviewData {
     pub created_at: DateTime<Utc>,
     /// Status
     pub status: ReviewStatus,
+    /// Reviewer's decision, set when the review is completed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub verdict: Option<Verdict>,
+    /// Files that were under review
+    #[serde(default)]
+    pub files: Vec<ReviewedFile>,
+    /// Unified diff applying all suggested changes to the reviewed content
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub suggestions_patch: Option<String>,
+    /// ID of the review this one re-reviews
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub previous_review: Option<String>,
+    /// Git tree of the reviewed content, kept under refs/hrevu/<id>
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tree: Option<String>,
+    /// Tree of the previous round, for the "changes since last review" view
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub previous_tree: Option<String>,
+}
+
+/// A reviewed file, as recorded in the final output
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReviewedFile {
+    pub path: String,
+    pub status: String,
+    /// Previous path for renamed or copied files
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub old_path: Option<String>,
+    /// Rename/copy similarity (0-100)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub similarity: Option<u16>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub binary: bool,
+    /// Blob OID before the change
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub old_oid: Option<String>,
+    /// Blob OID as reviewed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub new_oid: Option<String>,
+}
+
+impl From<&FileData> for ReviewedFile {
+    fn from(file: &FileData) -> Self {
+        Self {
+            path: file.path.clone(),
+            status: file.status.clone(),
+            old_path: file.old_path.clone(),
+            similarity: file.similarity,
+            binary: file.binary,
+            old_oid: file.old_oid.clone(),
+            new_oid: file.new_oid.clone(),
+        }
+    }
+}
+
+impl ReviewData {
+    /// Start a new review of the input
+    pub fn new(input_type: InputType) -> Self {
+        Self {
+            id: Uuid::new_v4().simple().to_string()[..8].to_string(),
+            input: input_type.display_title(),
+            input_type,
+            comments: Vec::new(),
+            created_at: Utc::now(),
+            status: ReviewStatus::InProgress,
+            verdict: None,
+            files: Vec::new(),
+            suggestions_patch: None,
+            previous_review: None,
+            tree: None,
+            previous_tree: None,
+        }
+    }
+
+    /// Find a comment or reply by ID
+    pub fn find_comment_mut(&mut self, id: &str) -> Option<&mut Comment> {
+        self.comments.iter_mut().find_map(|comment| {
+            if comment.id == id {
+                Some(comment)
+            } else {
+                comment.replies.iter_mut().find(|reply| reply.id == id)
+            }
+        })
+    }
+
+    /// Remove a comment (with its replies) or a single reply. Returns whether it was found.
+    pub fn remove_comment(&mut self, id: &str) -> bool {
+        if let Some(pos) = self.comments.iter().position(|c| c.id == id) {
+            self.comments.remove(pos);
+            return true;
+        }
+        self.comments.iter_mut().any(|comment| {
+            let count = comment.replies.len();
+            comment.replies.retain(|reply| reply.id != id);
+            comment.replies.len() != count
+        })
+    }
+
+    /// Comments still open, with their replies
+    pub fn open_comments(&self) -> Vec<Comment> {
+        self.comments
+            .iter()
+            .filter(|comment| comment.resolution.is_open())
+            .cloned()
+            .collect()
+    }
+
+    /// Process exit code: the verdict's code, or 130 (as for SIGINT) if cancelled
+    pub fn exit_code(&self) -> u8 {
+        match self.status {
+            ReviewStatus::Cancelled => 130,
+            _ => self.verdict.map_or(0, |verdict| verdict.exit_code()),
+        }
+    }
 }
 
 /// Input type
@@ -23,18 +140,73 @@ pub struct ReviewData {
 pub enum InputType {
     #[serde(rename = "commit_diff")]
     CommitDiff { commit: String },
+    #[serde(rename = "commit_range")]
+    CommitRange {
+        from: String,
+        to: String,
+        /// True for "A...B" (diff from the merge base), false for "A..B"
+        symmetric: bool,
+    },
+    #[serde(rename = "branch_diff")]
+    BranchDiff {
+        base: String,
+        /// Merge base of HEAD and the base branch
+        merge_base: String,
+        include_uncommitted: bool,
+        /// Show untracked (non-ignored) files as added files, with uncommitted changes
+        #[serde(default)]
+        include_untracked: bool,
+    },
     #[serde(rename = "file_content")]
     FileContent { path: String },
     #[serde(rename = "working_tree_diff")]
-    WorkingTreeDiff,
+    WorkingTreeDiff {
+        mode: WorkingTreeMode,
+        /// Show untracked (non-ignored) files as added files
+        include_untracked: bool,
+    },
+}
+
+/// Which working tree changes to review
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum WorkingTreeMode {
+    /// HEAD against the working tree, staged and unstaged together
+    #[default]
+    #[serde(rename = "combined")]
+    Combined,
+    /// HEAD against the index
+    #[serde(rename = "staged")]
+    Staged,
+    /// Index against the working tree
+    #[serde(rename = "unstaged")]
+    Unstaged,
 }
 
 impl InputType {
     pub fn display_title(&self) -> String {
         match self {
             InputType::CommitDiff { commit } => format!("Commit: {}", commit),
+            InputType::CommitRange { from, to, symmetric } => {
+                let op = if *symmetric { "..." } else { ".." };
+                format!("Commits: {}{}{}", from, op, to)
+            }
+            InputType::BranchDiff {
+                base,
+                include_uncommitted,
+                ..
+            } => {
+                if *include_uncommitted {
+                    format!("Branch vs {} (with uncommitted changes)", base)
+                } else {
+                    format!("Branch vs {}", base)
+                }
+            }
             InputType::FileContent { path } => format!("File: {}", path),
-            InputType::WorkingTreeDiff => "Current Changes".to_string(),
+            InputType::WorkingTreeDiff { mode, .. } => match mode {
+                WorkingTreeMode::Combined => "Current Changes".to_string(),
+                WorkingTreeMode::Staged => "Staged Changes".to_string(),
+                WorkingTreeMode::Unstaged => "Unstaged Changes".to_string(),
+            },
         }
     }
 }
@@ -47,34 +219,314 @@ pub struct Comment {
     /// File path (for diff mode)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub file: Option<String>,
-    /// Line number
+    /// Line number (first line of a range)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub line: Option<u32>,
+    /// First line of a multi-line range
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start_line: Option<u32>,
+    /// Last line of a multi-line range
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end_line: Option<u32>,
+    /// Start of a text selection: 0-based character offset into the first line
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start_column: Option<u32>,
+    /// End of a text selection (exclusive): 0-based character offset into the last line
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end_column: Option<u32>,
+    /// The selected text, quoted from the reviewed content
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub selected_text: Option<String>,
+    /// Replacement code proposed for the commented lines
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub suggestion: Option<Suggestion>,
+    /// Which side of the diff the line number refers to (none for global comments)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub side: Option<CommentSide>,
+    /// Category of the comment, e.g. blocker or nit
+    #[serde(default)]
+    pub kind: CommentKind,
+    /// Who wrote the comment
+    #[serde(default)]
+    pub author: CommentAuthor,
+    /// Whether the comment still needs work
+    #[serde(default, skip_serializing_if = "Resolution::is_open")]
+    pub resolution: Resolution,
     /// Comment content
     pub text: String,
     /// Creation time
     pub created_at: DateTime<Utc>,
+    /// The comment a reply answers (none for top-level comments)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent_id: Option<String>,
+    /// Replies in the order they were written
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub replies: Vec<Comment>,
 }
 
 impl Comment {
-    pub fn new(file: Option<String>, line: Option<u32>, text: String) -> Self {
+    /// Create a comment on an inclusive line range; single lines have start == end
+    pub fn new(
+        file: Option<String>,
+        lines: Option<(u32, u32)>,
+        side: Option<CommentSide>,
+        text: String,
+    ) -> Self {
+        let range = lines.filter(|(start, end)| start != end);
         Self {
             id: Uuid::new_v4().to_string(),
             file,
-            line,
+            line: lines.map(|(start, _)| start),
+            start_line: range.map(|(start, _)| start),
+            end_line: range.map(|(_, end)| end),
+            start_column: None,
+            end_column: None,
+            selected_text: None,
+            suggestion: None,
+            side,
+            kind: CommentKind::default(),
+            author: CommentAuthor::default(),
+            resolution: Resolution::default(),
             text,
             created_at: Utc::now(),
+            parent_id: None,
+            replies: Vec::new(),
         }
     }
+
+    /// Create a reply to this comment. Replies take their location and kind from the thread.
+    pub fn reply(&self, author: CommentAuthor, text: String) -> Self {
+        let mut reply = Self::new(None, None, None, text);
+        reply.kind = self.kind;
+        reply.author = author;
+        reply.parent_id = Some(self.id.clone());
+        reply
+    }
+
+    /// Inclusive line range covered by the comment
+    pub fn line_range(&self) -> Option<(u32, u32)> {
+        let start = self.start_line.or(self.line)?;
+        let end = self.end_line.unwrap_or(start);
+        Some((start, end))
+    }
 }
 
-/// Review status
+#[cfg(test)]
+impl Comment {
+    /// A new-side comment on `path` suggesting `replacement` for the `original` lines
+    pub fn suggesting(path: &str, start: u32, end: u32, original: &[&str], replacement: &str) -> Self {
+        let mut comment = Self::new(
+            Some(path.to_string()),
+            Some((start, end)),
+            Some(CommentSide::New),
+            String::new(),
+        );
+        comment.suggestion = Some(Suggestion {
+            original: original.iter().map(|line| line.to_string()).collect(),
+            replacement: replacement.to_string(),
+        });
+        comment
+    }
+}
+
+/// Suggested change: replaces the commented lines with new code
 #[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Suggestion {
+    /// The commented lines as they were reviewed
+    pub original: Vec<String>,
+    /// Replacement code (may be empty to delete the lines)
+    pub replacement: String,
+}
+
+/// Side of the diff a comment is anchored to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CommentSide {
+    /// Old line number (removed or context line)
+    #[serde(rename = "old")]
+    Old,
+    /// New line number (added or context line, or a line in file view)
+    #[serde(rename = "new")]
+    New,
+    /// The whole file, no line
+    #[serde(rename = "file")]
+    File,
+}
+
+impl CommentSide {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CommentSide::Old => "old",
+            CommentSide::New => "new",
+            CommentSide::File => "file",
+        }
+    }
+}
+
+/// Author of a comment
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CommentAuthor {
+    /// The human reviewer
+    #[serde(rename = "reviewer")]
+    #[default]
+    Reviewer,
+    /// The agent whose change is under review
+    #[serde(rename = "agent")]
+    Agent,
+}
+
+impl CommentAuthor {
+    pub fn label(&self) -> &'static str {
+        match self {
+            CommentAuthor::Reviewer => "Reviewer",
+            CommentAuthor::Agent => "Agent",
+        }
+    }
+}
+
+/// Resolution of a comment across review rounds
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Resolution {
+    #[serde(rename = "open")]
+    #[default]
+    Open,
+    /// The feedback was applied
+    #[serde(rename = "addressed")]
+    Addressed,
+    /// Deliberately not changed
+    #[serde(rename = "wont_fix")]
+    WontFix,
+}
+
+impl Resolution {
+    pub fn is_open(&self) -> bool {
+        *self == Resolution::Open
+    }
+}
+
+/// Agent's response to a comment, read from the file given to `hrevu rereview --replies`
+#[derive(Debug, Deserialize)]
+pub struct AgentResponse {
+    /// ID of the comment responded to
+    pub id: String,
+    pub resolution: Option<Resolution>,
+    /// Reply added to the comment's thread
+    pub reply: Option<String>,
+}
+
+/// Comment category, declared from most to least severe so sorting puts blockers first
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum CommentKind {
+    /// Must be fixed before the change can land
+    #[serde(rename = "blocker")]
+    Blocker,
+    /// Should be changed
+    #[serde(rename = "change_request")]
+    #[default]
+    ChangeRequest,
+    /// Needs an answer, not necessarily a change
+    #[serde(rename = "question")]
+    Question,
+    /// Minor, optional improvement
+    #[serde(rename = "nit")]
+    Nit,
+    /// Positive feedback, nothing to do
+    #[serde(rename = "praise")]
+    Praise,
+}
+
+impl CommentKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CommentKind::Blocker => "blocker",
+            CommentKind::ChangeRequest => "change_request",
+            CommentKind::Question => "question",
+            CommentKind::Nit => "nit",
+            CommentKind::Praise => "praise",
+        }
+    }
+
+    /// Name of a single comment's kind
+    pub fn name(&self) -> &'static str {
+        match self {
+            CommentKind::Blocker => "Blocker",
+            CommentKind::ChangeRequest => "Change request",
+            CommentKind::Question => "Question",
+            CommentKind::Nit => "Nit",
+            CommentKind::Praise => "Praise",
+        }
+    }
+
+    /// Heading used in the terminal summary
+    pub fn label(&self) -> &'static str {
+        match self {
+            CommentKind::Blocker => "Blockers",
+            CommentKind::ChangeRequest => "Change Requests",
+            CommentKind::Question => "Questions",
+            CommentKind::Nit => "Nits",
+            CommentKind::Praise => "Praise",
+        }
+    }
+
+    /// Severity in machine-readable reports (SARIF, Checkstyle, rdjson)
+    pub fn severity(&self) -> Severity {
+        match self {
+            CommentKind::Blocker => Severity::Error,
+            CommentKind::ChangeRequest => Severity::Warning,
+            CommentKind::Question | CommentKind::Nit | CommentKind::Praise => Severity::Info,
+        }
+    }
+}
+
+/// Severity of a comment kind, spelled by each report format in its own way
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+    Warning,
+    Info,
+}
+
+/// Review status
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum ReviewStatus {
     #[serde(rename = "in_progress")]
     InProgress,
     #[serde(rename = "completed")]
     Completed,
+    /// Abandoned from the UI or with Ctrl+C; comments are kept
+    #[serde(rename = "cancelled")]
+    Cancelled,
+}
+
+/// Reviewer's decision on the change
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Verdict {
+    #[serde(rename = "approve")]
+    Approve,
+    #[serde(rename = "request_changes")]
+    RequestChanges,
+    /// Feedback without approving or blocking
+    #[serde(rename = "comment")]
+    Comment,
+}
+
+impl Verdict {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Verdict::Approve => "Approved",
+            Verdict::RequestChanges => "Changes requested",
+            Verdict::Comment => "Commented",
+        }
+    }
+
+    /// Process exit code, so scripts and hooks can gate on the review.
+    /// 1 (errors) and 2 (invalid arguments) are left to failures.
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            Verdict::Approve => 0,
+            Verdict::RequestChanges => 10,
+            Verdict::Comment => 11,
+        }
+    }
 }
 
 /// API response - initial data
@@ -83,32 +535,113 @@ pub struct DataResponse {
     pub input_type: InputType,
     pub files: Vec<FileData>,
     pub comments: Vec<Comment>,
+    /// Changes since the previous round, when re-reviewing
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub interdiff: Option<Vec<FileData>>,
 }
 
 /// File data (for frontend rendering)
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, Serialize)]
 pub struct FileData {
     pub path: String,
     pub status: String,
+    /// Previous path for renamed or copied files
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub old_path: Option<String>,
+    /// Rename/copy similarity (0-100)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub similarity: Option<u16>,
+    /// Binary files have no lines and can only take file-level comments
+    pub binary: bool,
+    /// Size in bytes before the change
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub old_size: Option<u64>,
+    /// Size in bytes after the change
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub new_size: Option<u64>,
+    /// Blob OID before the change
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub old_oid: Option<String>,
+    /// Blob OID after the change
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub new_oid: Option<String>,
+    pub hunks: Vec<Hunk>,
+}
+
+impl FileData {
+    /// Find a line by its number on the given side
+    pub fn find_line(&self, side: CommentSide, number: u32) -> Option<&LineData> {
+        self.hunks
+            .iter()
+            .flat_map(|hunk| hunk.lines.iter())
+            .find(|line| line.number_on(side) == Some(number))
+    }
+}
+
+/// Diff hunk (file view uses a single hunk without header)
+#[derive(Debug, Clone, Serialize)]
+pub struct Hunk {
+    /// "@@ -a,b +c,d @@" header line, empty in file view
+    pub header: String,
+    pub old_start: u32,
+    pub old_lines: u32,
+    pub new_start: u32,
+    pub new_lines: u32,
     pub lines: Vec<LineData>,
 }
 
 /// Line data
 #[derive(Debug, Clone, Serialize)]
 pub struct LineData {
-    pub number: u32,
+    /// Line number in the old file (None for added lines)
+    pub old_number: Option<u32>,
+    /// Line number in the new file (None for removed lines)
+    pub new_number: Option<u32>,
     pub content: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     #[serde(rename = "type")]
     pub type_: Option<String>,
 }
 
+impl LineData {
+    /// Line number on the given side, if the line exists there
+    pub fn number_on(&self, side: CommentSide) -> Option<u32> {
+        match side {
+            CommentSide::Old => self.old_number,
+            CommentSide::New => self.new_number,
+            CommentSide::File => None,
+        }
+    }
+}
+
 /// API request - add comment
 #[derive(Debug, Deserialize)]
 pub struct AddCommentRequest {
     #[serde(rename = "file")]
     pub file: Option<String>,
     pub line: Option<u32>,
+    /// First line of a range, instead of `line`
+    pub start_line: Option<u32>,
+    /// Last line of a range, instead of `line`
+    pub end_line: Option<u32>,
+    /// Selection start, 0-based character offset into the first line
+    pub start_column: Option<u32>,
+    /// Selection end (exclusive), 0-based character offset into the last line
+    pub end_column: Option<u32>,
+    /// Replacement code for the commented lines
+    pub suggestion: Option<String>,
+    /// Defaults to "file" without a line and "new" with one
+    pub side: Option<CommentSide>,
+    /// Defaults to "change_request"
+    pub kind: Option<CommentKind>,
+    pub text: String,
+}
+
+/// API request - reply to a comment
+#[derive(Debug, Deserialize)]
+pub struct ReplyRequest {
+    /// Defaults to "reviewer"
+    pub author: Option<CommentAuthor>,
     pub text: String,
 }
 
@@ -117,11 +650,27 @@ pub struct AddCommentRequest {
 pub struct UpdateCommentRequest {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub text: Option<String>,
+    /// New replacement code; an empty string removes the suggestion
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub suggestion: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub kind: Option<CommentKind>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resolution: Option<Resolution>,
+}
+
+/// Complete review request
+#[derive(Debug, Deserialize)]
+pub struct CompleteRequest {
+    /// Defaults to "comment"
+    pub verdict: Option<Verdict>,
 }
 
 /// Completion response
 #[derive(Debug, Serialize)]
 pub struct CompletionResponse {
     pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub verdict: Option<Verdict>,
     pub comment_count: usize,
 }
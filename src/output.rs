@@ -1,109 +1,128 @@
-use colored::Colorize;
-use crate::models::ReviewData;
-use std::collections::HashMap;
+use anyhow::{Context, Result};
+use std::fmt;
 
-/// Print JSON formatted output
-pub fn print_json(data: &ReviewData) {
-    if let Ok(json) = serde_json::to_string_pretty(data) {
-        println!("{}", json);
-    }
-}
+use crate::cli::{OutputArgs, OutputFormat};
+use crate::models::{Comment, CommentSide, FileData, LineData, ReviewData};
+use crate::schema::ReviewOutput;
 
-/// Print comment summary (terminal format)
-pub fn print_summary(data: &ReviewData, file_contents: &HashMap<String, Vec<String>>) {
-    println!();
-    println!("{}", "═".repeat(60));
-    println!("{}", "📋 Review Summary".bold().cyan());
-    println!("{}", "═".repeat(60));
-    println!();
+mod checkstyle;
+mod markdown;
+mod quickfix;
+mod rdjson;
+mod sarif;
+mod text;
 
-    println!("{}: {}", "Input".bold(), data.input);
-    println!(
-        "{}: {}",
-        "Created".bold(),
-        data.created_at.format("%Y-%m-%d %H:%M:%S")
-    );
-    println!("{}: {}", "Comments".bold(), data.comments.len());
-    println!();
+/// Number of lines shown before a commented line
+const CONTEXT_LINES: usize = 3;
 
-    if data.comments.is_empty() {
-        println!("{}", "No comments added.".dimmed());
-        println!();
-        return;
+/// Render a finished review in the chosen format
+fn render_review(data: &ReviewData, files: &[FileData], format: OutputFormat) -> String {
+    match format {
+        OutputFormat::Text => text::render(data, files),
+        OutputFormat::Json => render_json(data),
+        OutputFormat::Markdown => markdown::render(data, files),
+        OutputFormat::Sarif => sarif::render(data),
+        OutputFormat::Quickfix => quickfix::render(data),
+        OutputFormat::Checkstyle => checkstyle::render(data),
+        OutputFormat::Rdjson => rdjson::render(data),
     }
+}
 
-    let mut by_file: std::collections::HashMap<Option<String>, Vec<&crate::models::Comment>> =
-        std::collections::HashMap::new();
-    for comment in &data.comments {
-        by_file.entry(comment.file.clone()).or_default().push(comment);
-    }
+/// Print a finished review to stdout, or write it to the file given with --output
+pub fn write_review(data: &ReviewData, files: &[FileData], output: &OutputArgs) -> Result<()> {
+    let Some(path) = &output.output_file else {
+        print!("{}", render_review(data, files, output.format()));
+        return Ok(());
+    };
 
-    for (file, comments) in by_file.iter() {
-        if let Some(f) = file {
-            println!("\n{}", format!("📄 {}", f).bold());
-        } else {
-            println!("\n{}", "💬 Global Comments".bold());
-        }
+    // Color codes would end up in the file
+    colored::control::set_override(false);
+    let rendered = render_review(data, files, output.format());
+    colored::control::unset_override();
+    std::fs::write(path, rendered).with_context(|| format!("Unable to write {}", path.display()))
+}
 
-        for comment in comments {
-            println!();
-            print!("💬 ");
+/// JSON output: the stable output format, with open comments sorted like the summary
+fn render_json(data: &ReviewData) -> String {
+    let open = data.open_comments();
+    let output = ReviewOutput::new(data, &sorted_comments(&open));
+    serde_json::to_string_pretty(&output).unwrap_or_default() + "\n"
+}
 
-            if let Some(line) = comment.line {
-                print!("{} {}: ", "Line".yellow(), line.to_string().yellow());
-            }
+/// Render into a String with `write!`-style code
+fn render_with(write: impl FnOnce(&mut String) -> fmt::Result) -> String {
+    let mut out = String::new();
+    write(&mut out).expect("writing to a String can't fail");
+    out
+}
 
-            println!("{}", comment.text);
+/// Comments ordered by kind (most severe first), then file and line.
+/// Global comments come before file comments of the same kind.
+fn sorted_comments(comments: &[Comment]) -> Vec<&Comment> {
+    let mut sorted: Vec<&Comment> = comments.iter().collect();
+    sorted.sort_by(|a, b| {
+        (a.kind, &a.file, a.line_range()).cmp(&(b.kind, &b.file, b.line_range()))
+    });
+    sorted
+}
 
-            if let (Some(file_path), Some(line_num)) = (&comment.file, comment.line) {
-                if let Some(lines) = file_contents.get(file_path) {
-                    let idx = (line_num as usize).saturating_sub(1);
+/// Comments ordered by file and line, then kind. Global comments come first.
+fn comments_by_location(comments: &[Comment]) -> Vec<&Comment> {
+    let mut sorted: Vec<&Comment> = comments.iter().collect();
+    sorted.sort_by(|a, b| {
+        (&a.file, a.line_range(), a.kind).cmp(&(&b.file, b.line_range(), b.kind))
+    });
+    sorted
+}
 
-                    let context_start = idx.saturating_sub(3);
-                    let context_end = idx;
+/// Path a comment's line numbers refer to: the old path of a renamed file
+/// for lines on the old side, the file's path otherwise
+fn location_path<'a>(data: &'a ReviewData, comment: &'a Comment) -> Option<&'a str> {
+    let path = comment.file.as_deref()?;
+    if comment.side != Some(CommentSide::Old) {
+        return Some(path);
+    }
+    let old_path = data
+        .files
+        .iter()
+        .find(|f| f.path == path)
+        .and_then(|f| f.old_path.as_deref());
+    Some(old_path.unwrap_or(path))
+}
 
-                    for i in context_start..context_end {
-                        if i < lines.len() {
-                            let line_num_display = i + 1;
-                            let content = lines[i].trim();
-                            if !content.is_empty() {
-                                println!(
-                                    "    {} {} {}",
-                                    (line_num_display as u32).to_string().dimmed(),
-                                    "│".dimmed(),
-                                    content.dimmed()
-                                );
-                            }
-                        }
-                    }
+/// First line a comment points at in line-based reports; file comments point at the top of the file
+fn location_line(comment: &Comment) -> u32 {
+    comment.line_range().map_or(1, |(start, _)| start)
+}
 
-                    if idx < lines.len() {
-                        let content = lines[idx].trim();
-                        if !content.is_empty() {
-                            println!(
-                                "    {} {} {}",
-                                line_num.to_string().yellow().bold(),
-                                "▸".yellow().bold(),
-                                content.yellow()
-                            );
-                        }
-                    }
-                }
-            }
+/// Line number shown for a diff line: the new number, or the old one for removed lines
+fn display_number(line: &LineData) -> u32 {
+    line.new_number.or(line.old_number).unwrap_or(0)
+}
 
-            println!(
-                "    {} {}",
-                "─".dimmed(),
-                comment.created_at.format("%H:%M").to_string().dimmed()
-            );
-        }
-    }
+/// Find the commented lines and the lines before them within the same hunk.
+/// A range that runs past its hunk is cut at the hunk's end.
+fn find_line_context<'a>(
+    files: &'a [FileData],
+    path: &str,
+    side: CommentSide,
+    start: u32,
+    end: u32,
+) -> Option<(&'a [LineData], &'a [LineData])> {
+    let file = files.iter().find(|f| f.path == path)?;
 
-    println!();
-    println!("{}", "─".repeat(60).dimmed());
-    println!(
-        "{} {} total comments",
-        "Summary:".bold(),
-        data.comments.len().to_string().cyan()
-    );
+    file.hunks.iter().find_map(|hunk| {
+        let first = hunk
+            .lines
+            .iter()
+            .position(|line| line.number_on(side) == Some(start))?;
+        let last = hunk
+            .lines
+            .iter()
+            .rposition(|line| line.number_on(side).is_some_and(|n| n <= end))
+            .unwrap_or(first)
+            .max(first);
+        let context_start = first.saturating_sub(CONTEXT_LINES);
+        Some((&hunk.lines[context_start..first], &hunk.lines[first..=last]))
+    })
 }
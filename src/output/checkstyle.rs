@@ -0,0 +1,71 @@
+use std::fmt::{self, Write};
+
+use super::{comments_by_location, location_line, location_path, render_with};
+use crate::models::{CommentKind, ReviewData, Severity};
+
+/// Render open comments as a Checkstyle XML report, one `<file>` per commented file.
+/// Checkstyle has no place for comments outside a file, so global comments are left out.
+pub fn render(data: &ReviewData) -> String {
+    render_with(|out| write_report(out, data))
+}
+
+fn write_report(out: &mut String, data: &ReviewData) -> fmt::Result {
+    writeln!(out, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+    writeln!(out, r#"<checkstyle version="4.3">"#)?;
+
+    let open = data.open_comments();
+    let located: Vec<_> = comments_by_location(&open)
+        .into_iter()
+        .filter_map(|comment| location_path(data, comment).map(|path| (path, comment)))
+        .collect();
+
+    for group in located.chunk_by(|a, b| a.0 == b.0) {
+        writeln!(out, r#"  <file name="{}">"#, escape(group[0].0))?;
+        for (_, comment) in group {
+            write!(out, r#"    <error line="{}""#, location_line(comment))?;
+            if let Some(column) = comment.start_column {
+                write!(out, r#" column="{}""#, column + 1)?;
+            }
+            writeln!(
+                out,
+                r#" severity="{}" message="{}" source="hrevu.{}"/>"#,
+                severity(comment.kind),
+                escape(&comment.text),
+                comment.kind.as_str()
+            )?;
+        }
+        writeln!(out, "  </file>")?;
+    }
+
+    writeln!(out, "</checkstyle>")
+}
+
+/// Checkstyle severity for a comment kind
+fn severity(kind: CommentKind) -> &'static str {
+    match kind.severity() {
+        Severity::Error => "error",
+        Severity::Warning => "warning",
+        Severity::Info => "info",
+    }
+}
+
+/// Escape text for an XML attribute, keeping line breaks
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            '\n' => escaped.push_str("&#10;"),
+            '\r' => escaped.push_str("&#13;"),
+            '\t' => escaped.push_str("&#9;"),
+            // Other control characters are not allowed in XML 1.0
+            c if c.is_control() => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
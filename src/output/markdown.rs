@@ -0,0 +1,138 @@
+use std::fmt::{self, Write};
+
+use super::{comments_by_location, display_number, find_line_context, render_with};
+use crate::models::{Comment, CommentSide, FileData, ReviewData, ReviewStatus};
+
+/// Render a finished review as Markdown: an overview, global comments,
+/// then one section per file with comments in line order
+pub fn render(data: &ReviewData, files: &[FileData]) -> String {
+    render_with(|out| write_review(out, data, files))
+}
+
+fn write_review(out: &mut String, data: &ReviewData, files: &[FileData]) -> fmt::Result {
+    writeln!(out, "# Review: {}", data.input)?;
+    writeln!(out)?;
+
+    if data.status == ReviewStatus::Cancelled {
+        writeln!(out, "- **Status:** Cancelled")?;
+    }
+    if let Some(verdict) = data.verdict {
+        writeln!(out, "- **Verdict:** {}", verdict.label())?;
+    }
+    writeln!(out, "- **Created:** {}", data.created_at.format("%Y-%m-%d %H:%M:%S"))?;
+
+    let open = data.open_comments();
+    let resolved = data.comments.len() - open.len();
+    if resolved > 0 {
+        writeln!(out, "- **Comments:** {} ({} resolved, not shown)", open.len(), resolved)?;
+    } else {
+        writeln!(out, "- **Comments:** {}", open.len())?;
+    }
+
+    for group in comments_by_location(&open).chunk_by(|a, b| a.file == b.file) {
+        writeln!(out)?;
+        match &group[0].file {
+            None => writeln!(out, "## General")?,
+            Some(path) => {
+                writeln!(out, "## `{}`", path)?;
+                if let Some(reviewed) = data.files.iter().find(|f| &f.path == path) {
+                    if let Some(old_path) = &reviewed.old_path {
+                        writeln!(out)?;
+                        writeln!(out, "_{} from `{}`_", reviewed.status, old_path)?;
+                    }
+                }
+            }
+        }
+
+        for comment in group {
+            write_comment(out, comment, files)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn write_comment(out: &mut String, comment: &Comment, files: &[FileData]) -> fmt::Result {
+    let side = comment.side.unwrap_or(CommentSide::New);
+    let range = comment.line_range();
+    let side_note = if side == CommentSide::Old { " (old)" } else { "" };
+
+    let location = match (range, &comment.file) {
+        (Some((start, end)), _) if start != end => Some(format!("Lines {}-{}{}", start, end, side_note)),
+        (Some((line, _)), _) => Some(format!("Line {}{}", line, side_note)),
+        (None, Some(_)) => Some("File".to_string()),
+        (None, None) => None,
+    };
+
+    writeln!(out)?;
+    match location {
+        Some(location) => writeln!(out, "### {} · {}", location, comment.kind.name())?,
+        None => writeln!(out, "### {}", comment.kind.name())?,
+    }
+    writeln!(out)?;
+    writeln!(out, "{}", comment.text)?;
+
+    if let Some(selected) = &comment.selected_text {
+        writeln!(out)?;
+        write_quoted(out, &format!("on `{}`", selected))?;
+    }
+
+    if let (Some(path), Some((start, end))) = (&comment.file, range) {
+        if let Some((context, targets)) = find_line_context(files, path, side, start, end) {
+            let mut code = String::new();
+            for line in context {
+                writeln!(code, "  {:>4} | {}", display_number(line), line.content)?;
+            }
+            for (target, number) in targets
+                .iter()
+                .filter_map(|line| line.number_on(side).map(|n| (line, n)))
+            {
+                writeln!(code, "> {:>4} | {}", number, target.content)?;
+            }
+            writeln!(out)?;
+            write_fenced(out, "", &code)?;
+        }
+    }
+
+    if let Some(suggestion) = &comment.suggestion {
+        let mut diff = String::new();
+        for line in &suggestion.original {
+            writeln!(diff, "-{}", line.trim_end_matches(['\r', '\n']))?;
+        }
+        for line in suggestion.replacement.lines() {
+            writeln!(diff, "+{}", line)?;
+        }
+        writeln!(out)?;
+        writeln!(out, "**Suggested change:**")?;
+        writeln!(out)?;
+        write_fenced(out, "diff", &diff)?;
+    }
+
+    for reply in &comment.replies {
+        writeln!(out)?;
+        write_quoted(out, &format!("**{}:** {}", reply.author.label(), reply.text))?;
+    }
+
+    Ok(())
+}
+
+/// Write text as a blockquote, quoting every line
+fn write_quoted(out: &mut String, text: &str) -> fmt::Result {
+    for line in text.lines() {
+        writeln!(out, "> {}", line)?;
+    }
+    Ok(())
+}
+
+/// Write a fenced code block, with a fence longer than any backtick run in the code
+fn write_fenced(out: &mut String, lang: &str, code: &str) -> fmt::Result {
+    let longest_run = code
+        .split(|c| c != '`')
+        .map(str::len)
+        .max()
+        .unwrap_or(0);
+    let fence = "`".repeat(longest_run.max(2) + 1);
+    writeln!(out, "{}{}", fence, lang)?;
+    write!(out, "{}", code)?;
+    writeln!(out, "{}", fence)
+}
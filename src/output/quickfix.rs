@@ -0,0 +1,36 @@
+use std::fmt::{self, Write};
+
+use super::{comments_by_location, location_line, location_path, render_with};
+use crate::models::{Comment, ReviewData};
+
+/// Render open comments as `file:line:col: kind: text` lines, in file and line order,
+/// for Vim's `:cfile` / `errorformat` and Emacs' compilation mode.
+/// Global comments have no location and are written as `kind: text`.
+pub fn render(data: &ReviewData) -> String {
+    render_with(|out| write_entries(out, data))
+}
+
+fn write_entries(out: &mut String, data: &ReviewData) -> fmt::Result {
+    let open = data.open_comments();
+    for comment in comments_by_location(&open) {
+        let text = format!("{}: {}", comment.kind.as_str(), single_line(comment));
+        match location_path(data, comment) {
+            Some(path) => {
+                let column = comment.start_column.map_or(1, |column| column + 1);
+                writeln!(out, "{}:{}:{}: {}", path, location_line(comment), column, text)?
+            }
+            None => writeln!(out, "{}", text)?,
+        }
+    }
+    Ok(())
+}
+
+/// The comment with its replies, joined into one line so each entry stays on its own line
+fn single_line(comment: &Comment) -> String {
+    let mut parts: Vec<String> = vec![comment.text.split_whitespace().collect::<Vec<_>>().join(" ")];
+    for reply in &comment.replies {
+        let text = reply.text.split_whitespace().collect::<Vec<_>>().join(" ");
+        parts.push(format!("[{}] {}", reply.author.label(), text));
+    }
+    parts.join(" ")
+}
@@ -0,0 +1,73 @@
+use serde_json::{json, Value};
+
+use super::{comments_by_location, location_path};
+use crate::models::{Comment, CommentKind, CommentSide, ReviewData, Severity};
+
+/// Render open comments in reviewdog's diagnostic format (rdjson), one diagnostic each.
+/// Columns are character offsets, which match reviewdog's byte columns on ASCII lines.
+pub fn render(data: &ReviewData) -> String {
+    let open = data.open_comments();
+    let diagnostics: Vec<Value> = comments_by_location(&open)
+        .into_iter()
+        .map(|comment| diagnostic(data, comment))
+        .collect();
+
+    let result = json!({
+        "source": { "name": "hrevu", "url": "https://github.com/alingse/human-review" },
+        "diagnostics": diagnostics,
+    });
+    serde_json::to_string_pretty(&result).unwrap_or_default() + "\n"
+}
+
+/// rdjson severity for a comment kind
+fn severity(kind: CommentKind) -> &'static str {
+    match kind.severity() {
+        Severity::Error => "ERROR",
+        Severity::Warning => "WARNING",
+        Severity::Info => "INFO",
+    }
+}
+
+fn diagnostic(data: &ReviewData, comment: &Comment) -> Value {
+    let mut diagnostic = json!({
+        "message": comment.text,
+        "severity": severity(comment.kind),
+        "code": { "value": comment.kind.as_str() },
+    });
+
+    // Global comments have no location
+    let Some(path) = location_path(data, comment) else {
+        return diagnostic;
+    };
+    let mut location = json!({ "path": path });
+
+    if let Some((start, end)) = comment.line_range() {
+        let mut range = json!({ "start": { "line": start }, "end": { "line": end } });
+        // rdjson columns are 1-based with an exclusive end, like the comment's
+        if let Some(column) = comment.start_column {
+            range["start"]["column"] = json!(column + 1);
+        }
+        if let Some(column) = comment.end_column {
+            range["end"]["column"] = json!(column + 1);
+        }
+        location["range"] = range;
+
+        // A suggestion replaces the whole commented lines of the new side
+        if let (Some(suggestion), Some(CommentSide::New)) = (&comment.suggestion, comment.side) {
+            let mut text = suggestion.replacement.clone();
+            if !text.is_empty() && !text.ends_with('\n') {
+                text.push('\n');
+            }
+            diagnostic["suggestions"] = json!([{
+                "range": {
+                    "start": { "line": start, "column": 1 },
+                    "end": { "line": end + 1, "column": 1 },
+                },
+                "text": text,
+            }]);
+        }
+    }
+
+    diagnostic["location"] = location;
+    diagnostic
+}
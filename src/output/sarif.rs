@@ -0,0 +1,137 @@
+use serde_json::{json, Value};
+
+use super::{location_path, sorted_comments};
+use crate::models::{Comment, CommentKind, ReviewData, Severity};
+
+const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+
+/// Rules, one per comment kind; a result's ruleIndex is its kind's position here
+const RULES: [CommentKind; 5] = [
+    CommentKind::Blocker,
+    CommentKind::ChangeRequest,
+    CommentKind::Question,
+    CommentKind::Nit,
+    CommentKind::Praise,
+];
+
+/// Render a finished review as a SARIF 2.1.0 log with one result per open comment
+pub fn render(data: &ReviewData) -> String {
+    let rules: Vec<Value> = RULES
+        .iter()
+        .map(|kind| {
+            json!({
+                "id": kind.as_str(),
+                "name": kind.name(),
+                "shortDescription": { "text": format!("{} from a human review", kind.name()) },
+                "defaultConfiguration": { "level": level(*kind) },
+            })
+        })
+        .collect();
+
+    let open = data.open_comments();
+    let results: Vec<Value> = sorted_comments(&open)
+        .into_iter()
+        .map(|comment| result(data, comment))
+        .collect();
+
+    let mut properties = json!({ "reviewId": data.id, "input": data.input });
+    if let Some(verdict) = data.verdict {
+        properties["verdict"] = json!(verdict);
+    }
+
+    let log = json!({
+        "$schema": SARIF_SCHEMA,
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": "hrevu",
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "informationUri": "https://github.com/alingse/human-review",
+                    "rules": rules,
+                }
+            },
+            "automationDetails": { "id": format!("hrevu/{}", data.id) },
+            // Selection columns count characters, not UTF-16 code units
+            "columnKind": "unicodeCodePoints",
+            "invocations": [{
+                "executionSuccessful": true,
+                "startTimeUtc": data.created_at,
+                "properties": { "status": data.status },
+            }],
+            "results": results,
+            "properties": properties,
+        }]
+    });
+
+    serde_json::to_string_pretty(&log).unwrap_or_default() + "\n"
+}
+
+/// SARIF level for a comment kind
+fn level(kind: CommentKind) -> &'static str {
+    match kind.severity() {
+        Severity::Error => "error",
+        Severity::Warning => "warning",
+        Severity::Info => "note",
+    }
+}
+
+fn result(data: &ReviewData, comment: &Comment) -> Value {
+    let kind = comment.kind;
+    let rule_index = RULES.iter().position(|rule| *rule == kind).unwrap_or(0);
+
+    let mut result = json!({
+        "ruleId": kind.as_str(),
+        "ruleIndex": rule_index,
+        "level": level(kind),
+        "message": { "text": comment.text },
+        "guid": comment.id,
+    });
+
+    // Global comments have no location
+    if let Some(location) = location(data, comment) {
+        result["locations"] = json!([location]);
+    }
+
+    let mut properties = json!({ "author": comment.author, "createdAt": comment.created_at });
+    if let Some(side) = comment.side {
+        properties["side"] = json!(side);
+    }
+    if let Some(suggestion) = &comment.suggestion {
+        properties["suggestion"] = json!(suggestion.replacement);
+    }
+    if !comment.replies.is_empty() {
+        let replies: Vec<Value> = comment
+            .replies
+            .iter()
+            .map(|reply| json!({ "author": reply.author, "text": reply.text }))
+            .collect();
+        properties["replies"] = json!(replies);
+    }
+    result["properties"] = properties;
+
+    result
+}
+
+/// Physical location of a comment: its file and, for line comments, the line range
+fn location(data: &ReviewData, comment: &Comment) -> Option<Value> {
+    let uri = location_path(data, comment)?;
+
+    let mut physical = json!({ "artifactLocation": { "uri": uri } });
+    if let Some((start, end)) = comment.line_range() {
+        let mut region = json!({ "startLine": start, "endLine": end });
+        // SARIF columns are 1-based; the end column is exclusive, as in the comment
+        if let Some(column) = comment.start_column {
+            region["startColumn"] = json!(column + 1);
+        }
+        if let Some(column) = comment.end_column {
+            region["endColumn"] = json!(column + 1);
+        }
+        if let Some(selected) = &comment.selected_text {
+            region["snippet"] = json!({ "text": selected });
+        }
+        physical["region"] = region;
+    }
+
+    Some(json!({ "physicalLocation": physical }))
+}
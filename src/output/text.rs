@@ -0,0 +1,222 @@
+use colored::Colorize;
+use std::fmt::{self, Write};
+
+use super::{display_number, find_line_context, render_with, sorted_comments};
+use crate::models::{Comment, CommentKind, CommentSide, FileData, ReviewData, ReviewStatus, Verdict};
+
+/// Render the comment summary (terminal format)
+pub fn render(data: &ReviewData, files: &[FileData]) -> String {
+    render_with(|out| write_summary(out, data, files))
+}
+
+fn write_summary(out: &mut String, data: &ReviewData, files: &[FileData]) -> fmt::Result {
+    writeln!(out)?;
+    writeln!(out, "{}", "═".repeat(60))?;
+    writeln!(out, "{}", "📋 Review Summary".bold().cyan())?;
+    writeln!(out, "{}", "═".repeat(60))?;
+    writeln!(out)?;
+
+    writeln!(out, "{}: {}", "Input".bold(), data.input)?;
+    writeln!(
+        out,
+        "{}: {}",
+        "Created".bold(),
+        data.created_at.format("%Y-%m-%d %H:%M:%S")
+    )?;
+    if data.status == ReviewStatus::Cancelled {
+        writeln!(out, "{}: {}", "Status".bold(), "Cancelled".yellow().bold())?;
+    }
+    if let Some(verdict) = data.verdict {
+        let label = match verdict {
+            Verdict::Approve => verdict.label().green(),
+            Verdict::RequestChanges => verdict.label().red(),
+            Verdict::Comment => verdict.label().yellow(),
+        };
+        writeln!(out, "{}: {}", "Verdict".bold(), label.bold())?;
+    }
+    if let Some(previous) = &data.previous_review {
+        writeln!(out, "{}: {}", "Previous review".bold(), previous)?;
+    }
+
+    // Resolved comments from earlier rounds need no more work, so only open ones are listed
+    let open = data.open_comments();
+    let resolved = data.comments.len() - open.len();
+    if resolved > 0 {
+        writeln!(
+            out,
+            "{}: {} {}",
+            "Comments".bold(),
+            open.len(),
+            format!("({} resolved, not shown)", resolved).dimmed()
+        )?;
+    } else {
+        writeln!(out, "{}: {}", "Comments".bold(), open.len())?;
+    }
+    writeln!(out)?;
+
+    if open.is_empty() {
+        let message = if resolved > 0 { "No open comments." } else { "No comments added." };
+        writeln!(out, "{}", message.dimmed())?;
+        return writeln!(out);
+    }
+
+    for kind_group in sorted_comments(&open).chunk_by(|a, b| a.kind == b.kind) {
+        let kind = kind_group[0].kind;
+        writeln!(
+            out,
+            "\n{} {}",
+            format!("{} {}", kind_icon(kind), kind.label()).bold().underline(),
+            format!("({})", kind_group.len()).dimmed()
+        )?;
+
+        for comments in kind_group.chunk_by(|a, b| a.file == b.file) {
+            write_file_header(out, data, comments[0].file.as_deref())?;
+            for comment in comments {
+                write_comment(out, comment, files)?;
+            }
+        }
+    }
+
+    writeln!(out)?;
+    writeln!(out, "{}", "─".repeat(60).dimmed())?;
+    writeln!(
+        out,
+        "{} {} open comments",
+        "Summary:".bold(),
+        open.len().to_string().cyan()
+    )
+}
+
+fn kind_icon(kind: CommentKind) -> &'static str {
+    match kind {
+        CommentKind::Blocker => "🛑",
+        CommentKind::ChangeRequest => "✏️",
+        CommentKind::Question => "❓",
+        CommentKind::Nit => "🔹",
+        CommentKind::Praise => "👍",
+    }
+}
+
+/// Write the heading for a file's comments, with rename info, or the global heading
+fn write_file_header(out: &mut String, data: &ReviewData, file: Option<&str>) -> fmt::Result {
+    let Some(f) = file else {
+        return writeln!(out, "\n{}", "💬 Global Comments".bold());
+    };
+
+    let reviewed = data.files.iter().find(|rf| rf.path == f);
+    let binary = if reviewed.is_some_and(|rf| rf.binary) {
+        " (binary)"
+    } else {
+        ""
+    };
+    writeln!(out, "\n{}", format!("📄 {}{}", f, binary).bold())?;
+    if let Some(reviewed) = reviewed {
+        if let Some(old_path) = &reviewed.old_path {
+            let similarity = reviewed
+                .similarity
+                .map(|s| format!(", {}% similar", s))
+                .unwrap_or_default();
+            writeln!(
+                out,
+                "{}",
+                format!("   {} from {}{}", reviewed.status, old_path, similarity).dimmed()
+            )?;
+        }
+    }
+    Ok(())
+}
+
+/// Write one comment with its location, quoted selection, context and suggestion
+fn write_comment(out: &mut String, comment: &Comment, files: &[FileData]) -> fmt::Result {
+    writeln!(out)?;
+    write!(out, "💬 ")?;
+
+    let side = comment.side.unwrap_or(CommentSide::New);
+    let range = comment.line_range();
+
+    match range {
+        Some((start, end)) if start != end => write!(
+            out,
+            "{} {} ({}): ",
+            "Lines".yellow(),
+            format!("{}-{}", start, end).yellow(),
+            side.as_str().yellow()
+        )?,
+        Some((line, _)) => write!(
+            out,
+            "{} {} ({}): ",
+            "Line".yellow(),
+            line.to_string().yellow(),
+            side.as_str().yellow()
+        )?,
+        None => {}
+    }
+
+    writeln!(out, "{}", comment.text)?;
+
+    if let Some(selected) = &comment.selected_text {
+        writeln!(out, "    {} \"{}\"", "on".dimmed(), selected.cyan())?;
+    }
+
+    if let (Some(file_path), Some((start, end))) = (&comment.file, range) {
+        if let Some((context, targets)) = find_line_context(files, file_path, side, start, end) {
+            for line in context {
+                let content = line.content.trim();
+                if !content.is_empty() {
+                    writeln!(
+                        out,
+                        "    {} {} {}",
+                        display_number(line).to_string().dimmed(),
+                        "│".dimmed(),
+                        content.dimmed()
+                    )?;
+                }
+            }
+
+            // Lines from the other side (e.g. removed lines in a new-side range) aren't part of it
+            for (target, number) in targets
+                .iter()
+                .filter_map(|line| line.number_on(side).map(|n| (line, n)))
+            {
+                writeln!(
+                    out,
+                    "    {} {} {}",
+                    number.to_string().yellow().bold(),
+                    "▸".yellow().bold(),
+                    target.content.trim().yellow()
+                )?;
+            }
+        }
+    }
+
+    if let Some(suggestion) = &comment.suggestion {
+        writeln!(out, "    {}", "Suggested change:".bold())?;
+        for line in &suggestion.original {
+            writeln!(out, "    {}", format!("- {}", line.trim_end_matches(['\r', '\n'])).red())?;
+        }
+        for line in suggestion.replacement.lines() {
+            writeln!(out, "    {}", format!("+ {}", line).green())?;
+        }
+    }
+
+    writeln!(
+        out,
+        "    {} {}",
+        "─".dimmed(),
+        comment.created_at.format("%H:%M").to_string().dimmed()
+    )?;
+
+    for reply in &comment.replies {
+        writeln!(
+            out,
+            "      {} {} {}",
+            "↳".dimmed(),
+            reply.author.label().bold(),
+            reply.created_at.format("%H:%M").to_string().dimmed()
+        )?;
+        for line in reply.text.lines() {
+            writeln!(out, "        {}", line)?;
+        }
+    }
+    Ok(())
+}
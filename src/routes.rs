@@ -4,11 +4,12 @@ use axum::{
     response::{IntoResponse, Response},
     Json,
 };
-use tracing::info;
+use tracing::{info, warn};
 
 use crate::models::*;
 use crate::server::{AppState, COMPLETION_SIGNAL, FINAL_DATA};
 use crate::git_ops;
+use crate::storage;
 
 /// Get initial data
 pub async fn get_data_handler(
@@ -16,16 +17,19 @@ pub async fn get_data_handler(
 ) -> Result<Json<DataResponse>, AppError> {
     let data = state.data.read().await;
 
-    let files = match &data.input_type {
-        InputType::CommitDiff { commit } => git_ops::get_commit_diff(commit)?,
-        InputType::FileContent { path } => git_ops::get_file_content(path)?,
-        InputType::WorkingTreeDiff => git_ops::get_working_tree_diff()?,
-    };
+    let files = git_ops::load_files(&data.input_type)?;
+    // Without the interdiff the review still works, just without the "since" view
+    let interdiff = data.previous_tree.as_deref().and_then(|tree| {
+        git_ops::get_interdiff(tree, &data.input_type, &files)
+            .inspect_err(|e| warn!("Failed to diff against the previous snapshot: {}", e))
+            .ok()
+    });
 
     let response = DataResponse {
         input_type: data.input_type.clone(),
         files,
         comments: data.comments.clone(),
+        interdiff,
     };
 
     Ok(Json(response))
@@ -38,14 +42,175 @@ pub async fn add_comment_handler(
 ) -> Result<Json<Comment>, AppError> {
     let mut data = state.data.write().await;
 
-    let comment = Comment::new(req.file, req.line, req.text);
+    let files = git_ops::load_files(&data.input_type)?;
+    let lines = line_range(&req)?;
+    let side = validate_anchor(&files, &req, lines)?;
+    let selected_text = select_text(&files, &req, lines, side)?;
+
+    let mut comment = Comment::new(req.file, lines, side, req.text);
+    comment.kind = req.kind.unwrap_or_default();
+    if selected_text.is_some() {
+        comment.start_column = req.start_column;
+        comment.end_column = req.end_column;
+        comment.selected_text = selected_text;
+    }
+    if let Some(replacement) = req.suggestion {
+        comment.suggestion = Some(Suggestion {
+            original: suggestion_original(&data.input_type, &files, &comment)?,
+            replacement,
+        });
+    }
     data.comments.push(comment.clone());
+    autosave(&data);
 
     info!("Added comment: {}", comment.id);
 
     Ok(Json(comment))
 }
 
+/// Resolve the requested line or start_line/end_line into an inclusive range
+fn line_range(req: &AddCommentRequest) -> Result<Option<(u32, u32)>, AppError> {
+    match (req.line, req.start_line, req.end_line) {
+        (None, None, None) => Ok(None),
+        (Some(line), None, None) => Ok(Some((line, line))),
+        (None, Some(start), Some(end)) if start <= end => Ok(Some((start, end))),
+        (None, Some(start), Some(end)) => Err(AppError::InvalidComment(format!(
+            "Invalid line range {}-{}",
+            start, end
+        ))),
+        _ => Err(AppError::InvalidComment(
+            "Give either a line or both start_line and end_line".to_string(),
+        )),
+    }
+}
+
+/// Check that a comment's file, lines and side exist in the reviewed files,
+/// and resolve the side to store
+fn validate_anchor(
+    files: &[FileData],
+    req: &AddCommentRequest,
+    lines: Option<(u32, u32)>,
+) -> Result<Option<CommentSide>, AppError> {
+    let Some(path) = &req.file else {
+        if lines.is_some() || req.side.is_some() {
+            return Err(AppError::InvalidComment(
+                "Global comments cannot have a line or side".to_string(),
+            ));
+        }
+        return Ok(None);
+    };
+
+    let file = files
+        .iter()
+        .find(|f| &f.path == path)
+        .ok_or_else(|| AppError::InvalidComment(format!("File not under review: {}", path)))?;
+
+    match (lines, req.side) {
+        (None, None | Some(CommentSide::File)) => Ok(Some(CommentSide::File)),
+        (None, Some(side)) => Err(AppError::InvalidComment(format!(
+            "Side '{}' needs a line",
+            side.as_str()
+        ))),
+        (Some(_), Some(CommentSide::File)) => Err(AppError::InvalidComment(
+            "File-level comments cannot have a line".to_string(),
+        )),
+        (Some((start, end)), side) => {
+            let side = side.unwrap_or(CommentSide::New);
+            if let Some(line) = (start..=end).find(|&line| file.find_line(side, line).is_none()) {
+                return Err(AppError::InvalidComment(format!(
+                    "Line {} is not on the {} side of {}",
+                    line,
+                    side.as_str(),
+                    path
+                )));
+            }
+            Ok(Some(side))
+        }
+    }
+}
+
+/// Quote the text between start_column on the first line and end_column on the last
+fn select_text(
+    files: &[FileData],
+    req: &AddCommentRequest,
+    lines: Option<(u32, u32)>,
+    side: Option<CommentSide>,
+) -> Result<Option<String>, AppError> {
+    let (start_col, end_col) = match (req.start_column, req.end_column) {
+        (None, None) => return Ok(None),
+        (Some(start), Some(end)) => (start as usize, end as usize),
+        _ => {
+            return Err(AppError::InvalidComment(
+                "Give both start_column and end_column".to_string(),
+            ))
+        }
+    };
+
+    let (Some(path), Some((start, end)), Some(side)) = (&req.file, lines, side) else {
+        return Err(AppError::InvalidComment("Columns need a line".to_string()));
+    };
+
+    let file = files.iter().find(|f| &f.path == path);
+    let contents: Vec<Vec<char>> = (start..=end)
+        .filter_map(|n| file.and_then(|f| f.find_line(side, n)))
+        .map(|line| line.content.chars().collect())
+        .collect();
+
+    let first_len = contents.first().map_or(0, |c| c.len());
+    let last_len = contents.last().map_or(0, |c| c.len());
+    if start_col > first_len || end_col > last_len || (start == end && start_col >= end_col) {
+        return Err(AppError::InvalidComment(format!(
+            "Invalid columns {}-{}",
+            start_col, end_col
+        )));
+    }
+
+    let last = contents.len() - 1;
+    let selected: Vec<String> = contents
+        .iter()
+        .enumerate()
+        .map(|(i, chars)| {
+            let from = if i == 0 { start_col } else { 0 };
+            let to = if i == last { end_col } else { chars.len() };
+            chars[from..to].iter().collect()
+        })
+        .collect();
+
+    Ok(Some(selected.join("\n")))
+}
+
+/// Capture the lines a suggestion replaces, byte for byte with their line endings,
+/// so patches match the file exactly. Suggestions only apply to the new side.
+fn suggestion_original(
+    input: &InputType,
+    files: &[FileData],
+    comment: &Comment,
+) -> Result<Vec<String>, AppError> {
+    let (Some(path), Some((start, end)), Some(CommentSide::New)) =
+        (&comment.file, comment.line_range(), comment.side)
+    else {
+        return Err(AppError::InvalidComment(
+            "Suggestions need lines on the new side".to_string(),
+        ));
+    };
+
+    let file = files
+        .iter()
+        .find(|f| &f.path == path)
+        .ok_or_else(|| AppError::InvalidComment(format!("File not under review: {}", path)))?;
+    let content = git_ops::reviewed_content(input, file)?;
+    let lines: Vec<&[u8]> = content.split_inclusive(|&b| b == b'\n').collect();
+
+    (start..=end)
+        .map(|n| {
+            lines
+                .get(n as usize - 1)
+                .map(|line| String::from_utf8_lossy(line).into_owned())
+                .ok_or_else(|| AppError::InvalidComment(format!("Line {} not found in {}", n, path)))
+        })
+        .collect()
+}
+
 /// Update comment
 pub async fn update_comment_handler(
     State(state): State<AppState>,
@@ -54,16 +219,69 @@ pub async fn update_comment_handler(
 ) -> Result<Json<Comment>, AppError> {
     let mut data = state.data.write().await;
 
-    data.comments
-        .iter_mut()
-        .find(|c| c.id == id)
-        .map(|comment| {
-            if let Some(text) = req.text {
-                comment.text = text;
+    let files = match &req.suggestion {
+        Some(replacement) if !replacement.is_empty() => git_ops::load_files(&data.input_type)?,
+        _ => Vec::new(),
+    };
+
+    let input_type = data.input_type.clone();
+    let comment = data
+        .find_comment_mut(&id)
+        .ok_or_else(|| AppError::CommentNotFound(id))?;
+
+    if let Some(text) = req.text {
+        comment.text = text;
+    }
+
+    if let Some(kind) = req.kind {
+        comment.kind = kind;
+    }
+
+    if let Some(resolution) = req.resolution {
+        comment.resolution = resolution;
+    }
+
+    match req.suggestion {
+        Some(replacement) if replacement.is_empty() => comment.suggestion = None,
+        Some(replacement) => match &mut comment.suggestion {
+            Some(suggestion) => suggestion.replacement = replacement,
+            None => {
+                comment.suggestion = Some(Suggestion {
+                    original: suggestion_original(&input_type, &files, comment)?,
+                    replacement,
+                })
             }
-            Json(comment.clone())
-        })
-        .ok_or_else(|| AppError::CommentNotFound(id))
+        },
+        None => {}
+    }
+
+    let comment = comment.clone();
+    autosave(&data);
+
+    Ok(Json(comment))
+}
+
+/// Reply to a comment. Replies to a reply join the same thread.
+pub async fn add_reply_handler(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Json(req): Json<ReplyRequest>,
+) -> Result<Json<Comment>, AppError> {
+    let mut data = state.data.write().await;
+
+    let thread = data
+        .comments
+        .iter_mut()
+        .find(|c| c.id == id || c.replies.iter().any(|reply| reply.id == id))
+        .ok_or_else(|| AppError::CommentNotFound(id))?;
+
+    let reply = thread.reply(req.author.unwrap_or_default(), req.text);
+    thread.replies.push(reply.clone());
+    autosave(&data);
+
+    info!("Added reply: {}", reply.id);
+
+    Ok(Json(reply))
 }
 
 /// Delete comment
@@ -73,8 +291,8 @@ pub async fn delete_comment_handler(
 ) -> Result<StatusCode, AppError> {
     let mut data = state.data.write().await;
 
-    if let Some(pos) = data.comments.iter().position(|c| c.id == id) {
-        data.comments.remove(pos);
+    if data.remove_comment(&id) {
+        autosave(&data);
         info!("Deleted comment: {}", id);
         Ok(StatusCode::NO_CONTENT)
     } else {
@@ -85,23 +303,55 @@ pub async fn delete_comment_handler(
 /// Complete review
 pub async fn complete_handler(
     State(state): State<AppState>,
+    Json(req): Json<CompleteRequest>,
 ) -> Result<Json<CompletionResponse>, AppError> {
     let mut data = state.data.write().await;
+    let verdict = req.verdict.unwrap_or(Verdict::Comment);
     data.status = ReviewStatus::Completed;
-
-    let _ = FINAL_DATA.set((*data).clone());
-    COMPLETION_SIGNAL.notify_one();
+    data.verdict = Some(verdict);
+    finish(&data);
 
     Ok(Json(CompletionResponse {
         message: "Review completed".to_string(),
+        verdict: Some(verdict),
+        comment_count: data.comments.len(),
+    }))
+}
+
+/// Cancel review, keeping the comments written so far
+pub async fn cancel_handler(
+    State(state): State<AppState>,
+) -> Result<Json<CompletionResponse>, AppError> {
+    let mut data = state.data.write().await;
+    data.status = ReviewStatus::Cancelled;
+    finish(&data);
+
+    Ok(Json(CompletionResponse {
+        message: "Review cancelled".to_string(),
+        verdict: None,
         comment_count: data.comments.len(),
     }))
 }
 
+/// Save the session after a comment change. A failed save shouldn't lose the comment,
+/// so it is only logged.
+fn autosave(data: &ReviewData) {
+    if let Err(e) = storage::save_session(data) {
+        warn!("Failed to save review session: {}", e);
+    }
+}
+
+/// Hand the final data to the waiting CLI
+fn finish(data: &ReviewData) {
+    let _ = FINAL_DATA.set(data.clone());
+    COMPLETION_SIGNAL.notify_one();
+}
+
 /// Application errors
 #[derive(Debug)]
 pub enum AppError {
     CommentNotFound(String),
+    InvalidComment(String),
     Internal(anyhow::Error),
 }
 
@@ -111,6 +361,7 @@ impl IntoResponse for AppError {
             AppError::CommentNotFound(id) => {
                 (StatusCode::NOT_FOUND, format!("Comment not found: {}", id))
             }
+            AppError::InvalidComment(message) => (StatusCode::BAD_REQUEST, message),
             AppError::Internal(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
         };
         (status, message).into_response()
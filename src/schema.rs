@@ -0,0 +1,717 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::models::{
+    Comment, CommentAuthor, CommentKind, CommentSide, InputType, Resolution, ReviewData,
+    ReviewStatus, ReviewedFile, Suggestion, Verdict, WorkingTreeMode,
+};
+
+/// Version of the JSON output format
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// A finished review, as printed by `--format json`.
+///
+/// The output types, enums included, are the documented contract for consumers of the
+/// JSON output, kept apart from the internal models so those can change freely. Changing
+/// their shape must bump `SCHEMA_VERSION` and update `json_schema`.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ReviewOutput {
+    /// Version of this format. Missing in output from before it was versioned.
+    #[serde(default)]
+    pub schema_version: u32,
+    /// Short review ID, for `hrevu show` and `hrevu rereview`
+    pub id: String,
+    /// What was reviewed, for display
+    pub input: String,
+    /// What was reviewed, as parsed from the command line
+    pub input_type: InputTypeOutput,
+    pub status: StatusOutput,
+    /// Reviewer's decision; missing if the review was cancelled
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub verdict: Option<VerdictOutput>,
+    pub created_at: DateTime<Utc>,
+    /// ID of the review this one re-reviews
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub previous_review: Option<String>,
+    /// Git tree of the reviewed content, kept under refs/hrevu/<id>
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tree: Option<String>,
+    /// Tree of the previous round
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub previous_tree: Option<String>,
+    /// Files that were under review
+    #[serde(default)]
+    pub files: Vec<FileOutput>,
+    /// Open comments, most severe first
+    pub comments: Vec<CommentOutput>,
+    /// Unified diff applying all suggested changes to the reviewed content
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub suggestions_patch: Option<String>,
+}
+
+/// A reviewed file
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FileOutput {
+    pub path: String,
+    /// e.g. "modified", "added", "renamed"
+    pub status: String,
+    /// Previous path for renamed or copied files
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub old_path: Option<String>,
+    /// Rename/copy similarity (0-100)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub similarity: Option<u16>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub binary: bool,
+    /// Blob OID before the change
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub old_oid: Option<String>,
+    /// Blob OID as reviewed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub new_oid: Option<String>,
+}
+
+/// A comment, or a reply within a comment's `replies`
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CommentOutput {
+    pub id: String,
+    /// File path; missing for global comments
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+    /// Line number (first line of a range)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub line: Option<u32>,
+    /// First line of a multi-line range
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub start_line: Option<u32>,
+    /// Last line of a multi-line range
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub end_line: Option<u32>,
+    /// Start of a text selection: 0-based character offset into the first line
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub start_column: Option<u32>,
+    /// End of a text selection (exclusive): 0-based character offset into the last line
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub end_column: Option<u32>,
+    /// Which side of the diff the line numbers refer to
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub side: Option<SideOutput>,
+    pub kind: KindOutput,
+    pub author: AuthorOutput,
+    pub resolution: ResolutionOutput,
+    pub text: String,
+    /// The selected text, quoted from the reviewed content
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub selected_text: Option<String>,
+    /// Replacement code proposed for the commented lines
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub suggestion: Option<SuggestionOutput>,
+    pub created_at: DateTime<Utc>,
+    /// The comment a reply answers
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub replies: Vec<CommentOutput>,
+}
+
+/// A suggested change, replacing the commented lines
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SuggestionOutput {
+    /// The commented lines as they were reviewed
+    pub original: Vec<String>,
+    /// Replacement code (empty to delete the lines)
+    pub replacement: String,
+}
+
+/// What was reviewed, tagged by `type`
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum InputTypeOutput {
+    CommitDiff {
+        commit: String,
+    },
+    CommitRange {
+        from: String,
+        to: String,
+        /// True for "A...B" (diff from the merge base), false for "A..B"
+        symmetric: bool,
+    },
+    BranchDiff {
+        base: String,
+        /// Merge base of HEAD and the base branch
+        merge_base: String,
+        include_uncommitted: bool,
+        /// Untracked files were shown, with uncommitted changes
+        #[serde(default)]
+        include_untracked: bool,
+    },
+    FileContent {
+        path: String,
+    },
+    WorkingTreeDiff {
+        mode: WorkingTreeModeOutput,
+        include_untracked: bool,
+    },
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WorkingTreeModeOutput {
+    Combined,
+    Staged,
+    Unstaged,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StatusOutput {
+    /// Only in saved sessions; printed reviews are completed or cancelled
+    InProgress,
+    Completed,
+    Cancelled,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VerdictOutput {
+    Approve,
+    RequestChanges,
+    Comment,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SideOutput {
+    Old,
+    New,
+    File,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KindOutput {
+    Blocker,
+    ChangeRequest,
+    Question,
+    Nit,
+    Praise,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuthorOutput {
+    Reviewer,
+    Agent,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ResolutionOutput {
+    Open,
+    Addressed,
+    WontFix,
+}
+
+/// `From` conversions both ways between a model enum and its output enum, variant by variant
+macro_rules! convert_enum {
+    ($model:ident <=> $output:ident { $($variant:ident),+ $(,)? }) => {
+        impl From<$model> for $output {
+            fn from(value: $model) -> Self {
+                match value {
+                    $($model::$variant => $output::$variant,)+
+                }
+            }
+        }
+
+        impl From<$output> for $model {
+            fn from(value: $output) -> Self {
+                match value {
+                    $($output::$variant => $model::$variant,)+
+                }
+            }
+        }
+    };
+}
+
+convert_enum!(WorkingTreeMode <=> WorkingTreeModeOutput { Combined, Staged, Unstaged });
+convert_enum!(ReviewStatus <=> StatusOutput { InProgress, Completed, Cancelled });
+convert_enum!(Verdict <=> VerdictOutput { Approve, RequestChanges, Comment });
+convert_enum!(CommentSide <=> SideOutput { Old, New, File });
+convert_enum!(CommentKind <=> KindOutput { Blocker, ChangeRequest, Question, Nit, Praise });
+convert_enum!(CommentAuthor <=> AuthorOutput { Reviewer, Agent });
+convert_enum!(Resolution <=> ResolutionOutput { Open, Addressed, WontFix });
+
+impl From<InputType> for InputTypeOutput {
+    fn from(input: InputType) -> Self {
+        match input {
+            InputType::CommitDiff { commit } => InputTypeOutput::CommitDiff { commit },
+            InputType::CommitRange { from, to, symmetric } => {
+                InputTypeOutput::CommitRange { from, to, symmetric }
+            }
+            InputType::BranchDiff {
+                base,
+                merge_base,
+                include_uncommitted,
+                include_untracked,
+            } => InputTypeOutput::BranchDiff {
+                base,
+                merge_base,
+                include_uncommitted,
+                include_untracked,
+            },
+            InputType::FileContent { path } => InputTypeOutput::FileContent { path },
+            InputType::WorkingTreeDiff {
+                mode,
+                include_untracked,
+            } => InputTypeOutput::WorkingTreeDiff {
+                mode: mode.into(),
+                include_untracked,
+            },
+        }
+    }
+}
+
+impl From<InputTypeOutput> for InputType {
+    fn from(input: InputTypeOutput) -> Self {
+        match input {
+            InputTypeOutput::CommitDiff { commit } => InputType::CommitDiff { commit },
+            InputTypeOutput::CommitRange { from, to, symmetric } => {
+                InputType::CommitRange { from, to, symmetric }
+            }
+            InputTypeOutput::BranchDiff {
+                base,
+                merge_base,
+                include_uncommitted,
+                include_untracked,
+            } => InputType::BranchDiff {
+                base,
+                merge_base,
+                include_uncommitted,
+                include_untracked,
+            },
+            InputTypeOutput::FileContent { path } => InputType::FileContent { path },
+            InputTypeOutput::WorkingTreeDiff {
+                mode,
+                include_untracked,
+            } => InputType::WorkingTreeDiff {
+                mode: mode.into(),
+                include_untracked,
+            },
+        }
+    }
+}
+
+impl ReviewOutput {
+    /// Output for a review, listing the given comments
+    pub fn new(data: &ReviewData, comments: &[&Comment]) -> Self {
+        Self {
+            schema_version: SCHEMA_VERSION,
+            id: data.id.clone(),
+            input: data.input.clone(),
+            input_type: data.input_type.clone().into(),
+            status: data.status.into(),
+            verdict: data.verdict.map(VerdictOutput::from),
+            created_at: data.created_at,
+            previous_review: data.previous_review.clone(),
+            tree: data.tree.clone(),
+            previous_tree: data.previous_tree.clone(),
+            files: data.files.iter().map(FileOutput::from).collect(),
+            comments: comments.iter().map(|comment| CommentOutput::from(*comment)).collect(),
+            suggestions_patch: data.suggestions_patch.clone(),
+        }
+    }
+}
+
+/// Read back JSON output, e.g. for `hrevu apply` and `hrevu since`
+impl From<ReviewOutput> for ReviewData {
+    fn from(output: ReviewOutput) -> Self {
+        Self {
+            id: output.id,
+            input_type: output.input_type.into(),
+            input: output.input,
+            comments: output.comments.into_iter().map(Comment::from).collect(),
+            created_at: output.created_at,
+            status: output.status.into(),
+            verdict: output.verdict.map(Verdict::from),
+            files: output.files.into_iter().map(ReviewedFile::from).collect(),
+            suggestions_patch: output.suggestions_patch,
+            previous_review: output.previous_review,
+            tree: output.tree,
+            previous_tree: output.previous_tree,
+        }
+    }
+}
+
+impl From<&ReviewedFile> for FileOutput {
+    fn from(file: &ReviewedFile) -> Self {
+        Self {
+            path: file.path.clone(),
+            status: file.status.clone(),
+            old_path: file.old_path.clone(),
+            similarity: file.similarity,
+            binary: file.binary,
+            old_oid: file.old_oid.clone(),
+            new_oid: file.new_oid.clone(),
+        }
+    }
+}
+
+impl From<FileOutput> for ReviewedFile {
+    fn from(file: FileOutput) -> Self {
+        Self {
+            path: file.path,
+            status: file.status,
+            old_path: file.old_path,
+            similarity: file.similarity,
+            binary: file.binary,
+            old_oid: file.old_oid,
+            new_oid: file.new_oid,
+        }
+    }
+}
+
+impl From<&Comment> for CommentOutput {
+    fn from(comment: &Comment) -> Self {
+        Self {
+            id: comment.id.clone(),
+            file: comment.file.clone(),
+            line: comment.line,
+            start_line: comment.start_line,
+            end_line: comment.end_line,
+            start_column: comment.start_column,
+            end_column: comment.end_column,
+            side: comment.side.map(SideOutput::from),
+            kind: comment.kind.into(),
+            author: comment.author.into(),
+            resolution: comment.resolution.into(),
+            text: comment.text.clone(),
+            selected_text: comment.selected_text.clone(),
+            suggestion: comment.suggestion.as_ref().map(|suggestion| SuggestionOutput {
+                original: suggestion.original.clone(),
+                replacement: suggestion.replacement.clone(),
+            }),
+            created_at: comment.created_at,
+            parent_id: comment.parent_id.clone(),
+            replies: comment.replies.iter().map(CommentOutput::from).collect(),
+        }
+    }
+}
+
+impl From<CommentOutput> for Comment {
+    fn from(comment: CommentOutput) -> Self {
+        Self {
+            id: comment.id,
+            file: comment.file,
+            line: comment.line,
+            start_line: comment.start_line,
+            end_line: comment.end_line,
+            start_column: comment.start_column,
+            end_column: comment.end_column,
+            selected_text: comment.selected_text,
+            suggestion: comment.suggestion.map(|suggestion| Suggestion {
+                original: suggestion.original,
+                replacement: suggestion.replacement,
+            }),
+            side: comment.side.map(CommentSide::from),
+            kind: comment.kind.into(),
+            author: comment.author.into(),
+            resolution: comment.resolution.into(),
+            text: comment.text,
+            created_at: comment.created_at,
+            parent_id: comment.parent_id,
+            replies: comment.replies.into_iter().map(Comment::from).collect(),
+        }
+    }
+}
+
+/// JSON Schema (draft 2020-12) of the JSON output, matching `ReviewOutput`
+pub fn json_schema() -> Value {
+    let line = json!({ "type": "integer", "minimum": 1 });
+    let column = json!({ "type": "integer", "minimum": 0 });
+    let timestamp = json!({ "type": "string", "format": "date-time" });
+
+    json!({
+        "$schema": "https://json-schema.org/draft/2020-12/schema",
+        "$id": format!("https://github.com/alingse/human-review/schema/v{}.json", SCHEMA_VERSION),
+        "title": "hrevu review",
+        "description": "A finished hrevu review, as printed by `hrevu --format json`",
+        "type": "object",
+        "required": ["schema_version", "id", "input", "input_type", "status", "created_at", "files", "comments"],
+        "properties": {
+            "schema_version": { "const": SCHEMA_VERSION, "description": "Version of this format" },
+            "id": { "type": "string", "description": "Short review ID, for `hrevu show` and `hrevu rereview`" },
+            "input": { "type": "string", "description": "What was reviewed, for display" },
+            "input_type": { "$ref": "#/$defs/input_type" },
+            "status": { "enum": ["completed", "cancelled", "in_progress"] },
+            "verdict": {
+                "enum": ["approve", "request_changes", "comment"],
+                "description": "Reviewer's decision; missing if the review was cancelled",
+            },
+            "created_at": timestamp,
+            "previous_review": { "type": "string", "description": "ID of the review this one re-reviews" },
+            "tree": { "type": "string", "description": "Git tree of the reviewed content, kept under refs/hrevu/<id>" },
+            "previous_tree": { "type": "string", "description": "Tree of the previous round" },
+            "files": { "type": "array", "items": { "$ref": "#/$defs/file" } },
+            "comments": {
+                "type": "array",
+                "items": { "$ref": "#/$defs/comment" },
+                "description": "Open comments, most severe first",
+            },
+            "suggestions_patch": {
+                "type": "string",
+                "description": "Unified diff applying all suggested changes to the reviewed content",
+            },
+        },
+        "$defs": {
+            "input_type": {
+                "type": "object",
+                "required": ["type"],
+                "oneOf": [
+                    {
+                        "properties": { "type": { "const": "commit_diff" }, "commit": { "type": "string" } },
+                        "required": ["commit"],
+                    },
+                    {
+                        "properties": {
+                            "type": { "const": "commit_range" },
+                            "from": { "type": "string" },
+                            "to": { "type": "string" },
+                            "symmetric": { "type": "boolean" },
+                        },
+                        "required": ["from", "to", "symmetric"],
+                    },
+                    {
+                        "properties": {
+                            "type": { "const": "branch_diff" },
+                            "base": { "type": "string" },
+                            "merge_base": { "type": "string" },
+                            "include_uncommitted": { "type": "boolean" },
+                            "include_untracked": { "type": "boolean" },
+                        },
+                        "required": ["base", "merge_base", "include_uncommitted", "include_untracked"],
+                    },
+                    {
+                        "properties": { "type": { "const": "file_content" }, "path": { "type": "string" } },
+                        "required": ["path"],
+                    },
+                    {
+                        "properties": {
+                            "type": { "const": "working_tree_diff" },
+                            "mode": { "enum": ["combined", "staged", "unstaged"] },
+                            "include_untracked": { "type": "boolean" },
+                        },
+                        "required": ["mode", "include_untracked"],
+                    },
+                ],
+            },
+            "file": {
+                "type": "object",
+                "required": ["path", "status"],
+                "properties": {
+                    "path": { "type": "string" },
+                    "status": { "type": "string", "description": "e.g. modified, added, renamed" },
+                    "old_path": { "type": "string", "description": "Previous path for renamed or copied files" },
+                    "similarity": { "type": "integer", "minimum": 0, "maximum": 100 },
+                    "binary": { "type": "boolean" },
+                    "old_oid": { "type": "string", "description": "Blob OID before the change" },
+                    "new_oid": { "type": "string", "description": "Blob OID as reviewed" },
+                },
+            },
+            "comment": {
+                "type": "object",
+                "required": ["id", "kind", "author", "resolution", "text", "created_at"],
+                "properties": {
+                    "id": { "type": "string" },
+                    "file": { "type": "string", "description": "File path; missing for global comments" },
+                    "line": line.clone(),
+                    "start_line": line.clone(),
+                    "end_line": line,
+                    "start_column": column.clone(),
+                    "end_column": column,
+                    "side": {
+                        "enum": ["old", "new", "file"],
+                        "description": "Which side of the diff the line numbers refer to",
+                    },
+                    "kind": { "enum": ["blocker", "change_request", "question", "nit", "praise"] },
+                    "author": { "enum": ["reviewer", "agent"] },
+                    "resolution": { "enum": ["open", "addressed", "wont_fix"] },
+                    "text": { "type": "string" },
+                    "selected_text": { "type": "string" },
+                    "suggestion": {
+                        "type": "object",
+                        "required": ["original", "replacement"],
+                        "properties": {
+                            "original": { "type": "array", "items": { "type": "string" } },
+                            "replacement": { "type": "string" },
+                        },
+                    },
+                    "created_at": timestamp,
+                    "parent_id": { "type": "string", "description": "The comment a reply answers" },
+                    "replies": { "type": "array", "items": { "$ref": "#/$defs/comment" } },
+                },
+            },
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Check a value against the subset of JSON Schema that `json_schema` uses.
+    /// Objects may only have the properties the schema lists.
+    fn check(value: &Value, schema: &Value, root: &Value, at: &str) -> Result<(), String> {
+        if let Some(reference) = schema.get("$ref").and_then(Value::as_str) {
+            let name = reference.trim_start_matches("#/$defs/");
+            return check(value, &root["$defs"][name], root, at);
+        }
+        if let Some(expected) = schema.get("const") {
+            if value != expected {
+                return Err(format!("{}: {} is not {}", at, value, expected));
+            }
+        }
+        if let Some(allowed) = schema.get("enum").and_then(Value::as_array) {
+            if !allowed.contains(value) {
+                return Err(format!("{}: {} is not one of {:?}", at, value, allowed));
+            }
+        }
+        if let Some(kind) = schema.get("type").and_then(Value::as_str) {
+            let matches = match kind {
+                "object" => value.is_object(),
+                "array" => value.is_array(),
+                "string" => value.is_string(),
+                "integer" => value.is_u64() || value.is_i64(),
+                "boolean" => value.is_boolean(),
+                _ => return Err(format!("{}: unsupported type {}", at, kind)),
+            };
+            if !matches {
+                return Err(format!("{}: {} is not a {}", at, value, kind));
+            }
+        }
+        if let (Some(n), Some(min)) = (value.as_f64(), schema.get("minimum").and_then(Value::as_f64)) {
+            if n < min {
+                return Err(format!("{}: {} is below {}", at, n, min));
+            }
+        }
+        if let (Some(n), Some(max)) = (value.as_f64(), schema.get("maximum").and_then(Value::as_f64)) {
+            if n > max {
+                return Err(format!("{}: {} is above {}", at, n, max));
+            }
+        }
+        if let (Some(items), Some(item_schema)) = (value.as_array(), schema.get("items")) {
+            for (i, item) in items.iter().enumerate() {
+                check(item, item_schema, root, &format!("{}[{}]", at, i))?;
+            }
+        }
+
+        let Some(object) = value.as_object() else {
+            return Ok(());
+        };
+        if let Some(branches) = schema.get("oneOf").and_then(Value::as_array) {
+            let matching = branches
+                .iter()
+                .filter(|branch| check(value, branch, root, at).is_ok())
+                .count();
+            if matching != 1 {
+                return Err(format!("{}: matches {} of the oneOf branches", at, matching));
+            }
+        }
+        for key in schema["required"].as_array().into_iter().flatten() {
+            let key = key.as_str().unwrap();
+            if !object.contains_key(key) {
+                return Err(format!("{}: missing {}", at, key));
+            }
+        }
+        for (key, field) in object {
+            let in_branch = schema["oneOf"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .any(|branch| branch["properties"].get(key).is_some());
+            match schema["properties"].get(key) {
+                Some(field_schema) => check(field, field_schema, root, &format!("{}.{}", at, key))?,
+                None if in_branch => {}
+                None => return Err(format!("{}: {} is not in the schema", at, key)),
+            }
+        }
+        Ok(())
+    }
+
+    /// A review using every optional field of the output
+    fn populated_review(input_type: InputType) -> ReviewData {
+        let mut data = ReviewData::new(input_type);
+        data.status = ReviewStatus::Completed;
+        data.verdict = Some(Verdict::RequestChanges);
+        data.previous_review = Some("0123abcd".to_string());
+        data.tree = Some("a".repeat(40));
+        data.previous_tree = Some("b".repeat(40));
+        data.suggestions_patch = Some("diff --git a/src/lib.rs b/src/lib.rs\n".to_string());
+        data.files = vec![ReviewedFile {
+            path: "src/lib.rs".to_string(),
+            status: "renamed".to_string(),
+            old_path: Some("src/old.rs".to_string()),
+            similarity: Some(90),
+            binary: true,
+            old_oid: Some("c".repeat(40)),
+            new_oid: Some("d".repeat(40)),
+        }];
+
+        let mut comment = Comment::new(
+            Some("src/lib.rs".to_string()),
+            Some((3, 4)),
+            Some(CommentSide::New),
+            "Rename this".to_string(),
+        );
+        comment.kind = CommentKind::Blocker;
+        comment.start_column = Some(0);
+        comment.end_column = Some(5);
+        comment.selected_text = Some("let x".to_string());
+        comment.resolution = Resolution::WontFix;
+        comment.suggestion = Some(Suggestion {
+            original: vec!["let x = 1;\n".to_string(), "let y = 2;\n".to_string()],
+            replacement: "let count = 1;".to_string(),
+        });
+        let reply = comment.reply(CommentAuthor::Agent, "Kept for compatibility".to_string());
+        comment.replies.push(reply);
+
+        let global = Comment::new(None, None, None, "Looks good overall".to_string());
+        data.comments = vec![comment, global];
+        data
+    }
+
+    #[test]
+    fn output_matches_json_schema() {
+        let schema = json_schema();
+        let inputs = [
+            InputType::CommitDiff { commit: "HEAD".to_string() },
+            InputType::CommitRange { from: "a".to_string(), to: "b".to_string(), symmetric: true },
+            InputType::BranchDiff {
+                base: "main".to_string(),
+                merge_base: "e".repeat(40),
+                include_uncommitted: true,
+                include_untracked: true,
+            },
+            InputType::FileContent { path: "src/lib.rs".to_string() },
+            InputType::WorkingTreeDiff { mode: WorkingTreeMode::Staged, include_untracked: true },
+        ];
+
+        for input in inputs {
+            let data = populated_review(input);
+            let comments: Vec<&Comment> = data.comments.iter().collect();
+            let value = serde_json::to_value(ReviewOutput::new(&data, &comments)).unwrap();
+            if let Err(e) = check(&value, &schema, &schema, "$") {
+                panic!("{}\n{}", e, serde_json::to_string_pretty(&value).unwrap());
+            }
+        }
+    }
+
+    #[test]
+    fn schema_check_rejects_unknown_fields() {
+        let schema = json_schema();
+        let data = populated_review(InputType::CommitDiff { commit: "HEAD".to_string() });
+        let mut value = serde_json::to_value(ReviewOutput::new(&data, &[])).unwrap();
+        value["undocumented"] = json!(true);
+        assert!(check(&value, &schema, &schema, "$").is_err());
+    }
+}
@@ -11,9 +11,9 @@ use std::sync::Arc;
 use std::time::Duration;
 use tokio::sync::RwLock;
 use tower_http::cors::CorsLayer;
-use tracing::info;
+use tracing::{info, warn};
 
-use crate::models::ReviewData;
+use crate::models::{ReviewData, ReviewStatus};
 use crate::routes::*;
 use crate::static_assets;
 
@@ -26,23 +26,29 @@ pub struct AppState {
     pub data: Arc<RwLock<ReviewData>>,
 }
 
+impl AppState {
+    pub fn new(data: ReviewData) -> Self {
+        Self {
+            data: Arc::new(RwLock::new(data)),
+        }
+    }
+}
+
 /// Global completion signal and data storage
 pub static COMPLETION_SIGNAL: tokio::sync::Notify = tokio::sync::Notify::const_new();
 pub static FINAL_DATA: tokio::sync::OnceCell<ReviewData> = tokio::sync::OnceCell::const_new();
 
 /// Run server
-pub async fn run(port: u16, data: ReviewData) -> Result<u16> {
-    let state = AppState {
-        data: Arc::new(RwLock::new(data)),
-    };
-
+pub async fn run(port: u16, state: AppState) -> Result<u16> {
     let app = Router::new()
         .route("/", get(index_handler))
         .route("/api/data", get(get_data_handler))
         .route("/api/comments", post(add_comment_handler))
         .route("/api/comments/:id", put(update_comment_handler))
         .route("/api/comments/:id", delete(delete_comment_handler))
+        .route("/api/comments/:id/replies", post(add_reply_handler))
         .route("/api/complete", post(complete_handler))
+        .route("/api/cancel", post(cancel_handler))
         .route("/static/*path", get(serve_static_handler))
         .layer(CorsLayer::permissive())
         .with_state(state);
@@ -60,9 +66,22 @@ pub async fn run(port: u16, data: ReviewData) -> Result<u16> {
     Ok(actual_port)
 }
 
-/// Wait for completion signal and return final data
-pub async fn wait_for_completion() -> Result<ReviewData> {
-    COMPLETION_SIGNAL.notified().await;
+/// Wait for completion signal and return final data.
+/// Ctrl+C cancels the review and returns the comments written so far.
+pub async fn wait_for_completion(state: &AppState) -> Result<ReviewData> {
+    tokio::select! {
+        _ = COMPLETION_SIGNAL.notified() => {}
+        result = tokio::signal::ctrl_c() => {
+            if let Err(e) = result {
+                warn!("Failed to listen for Ctrl+C: {}", e);
+                COMPLETION_SIGNAL.notified().await;
+            } else {
+                let mut data = state.data.write().await;
+                data.status = ReviewStatus::Cancelled;
+                return Ok(data.clone());
+            }
+        }
+    }
     tokio::time::sleep(Duration::from_millis(COMPLETION_WAIT_MS)).await;
 
     FINAL_DATA
@@ -0,0 +1,128 @@
+use anyhow::{Context, Result};
+use git2::Repository;
+use std::path::{Path, PathBuf};
+use tracing::warn;
+
+use crate::models::ReviewData;
+use crate::schema::{ReviewOutput, SCHEMA_VERSION};
+
+/// Directory under the git dir (or the temp dir outside a repository) holding hrevu's files
+fn hrevu_dir() -> PathBuf {
+    match Repository::discover(".") {
+        Ok(repo) => repo.path().join("hrevu"),
+        Err(_) => std::env::temp_dir().join("hrevu"),
+    }
+}
+
+/// Directory for in-progress review sessions
+fn sessions_dir() -> PathBuf {
+    hrevu_dir().join("sessions")
+}
+
+/// Directory for finished reviews
+fn reviews_dir() -> PathBuf {
+    hrevu_dir().join("reviews")
+}
+
+/// Path of the session file for a review
+pub fn session_path(id: &str) -> PathBuf {
+    sessions_dir().join(format!("{}.json", id))
+}
+
+/// Write a review to a JSON file, replacing it atomically so a crash never leaves half a file
+fn write_review(path: &Path, data: &ReviewData) -> Result<()> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)
+            .with_context(|| format!("Unable to create {}", dir.display()))?;
+    }
+
+    let json = serde_json::to_string_pretty(data)?;
+    let tmp = path.with_extension("json.tmp");
+    std::fs::write(&tmp, json).with_context(|| format!("Unable to write {}", tmp.display()))?;
+    std::fs::rename(&tmp, path).with_context(|| format!("Unable to write {}", path.display()))?;
+    Ok(())
+}
+
+/// Read a review from a JSON file
+fn read_review(path: &Path) -> Result<ReviewData> {
+    let json = std::fs::read_to_string(path)
+        .with_context(|| format!("Unable to read {}", path.display()))?;
+    serde_json::from_str(&json)
+        .with_context(|| format!("{} is not a hrevu review", path.display()))
+}
+
+/// Save an in-progress review so it can be resumed
+pub fn save_session(data: &ReviewData) -> Result<()> {
+    write_review(&session_path(&data.id), data)
+}
+
+/// Load a saved session
+pub fn load_session(id: &str) -> Result<ReviewData> {
+    let path = session_path(id);
+    if !path.exists() {
+        anyhow::bail!("No review session '{}' in {}", id, sessions_dir().display());
+    }
+    read_review(&path)
+}
+
+/// Remove a session once its review is finished
+pub fn remove_session(id: &str) -> Result<()> {
+    let path = session_path(id);
+    if path.exists() {
+        std::fs::remove_file(&path)
+            .with_context(|| format!("Unable to remove {}", path.display()))?;
+    }
+    Ok(())
+}
+
+/// Keep a finished review (completed or cancelled) in the local history
+pub fn archive_review(data: &ReviewData) -> Result<()> {
+    write_review(&reviews_dir().join(format!("{}.json", data.id)), data)
+}
+
+/// Load a review from the history
+pub fn load_review(id: &str) -> Result<ReviewData> {
+    let path = reviews_dir().join(format!("{}.json", id));
+    if !path.exists() {
+        anyhow::bail!("No review '{}' in {}", id, reviews_dir().display());
+    }
+    read_review(&path)
+}
+
+/// Load a review from a JSON file written by `hrevu --json`
+pub fn load_review_file(path: &str) -> Result<ReviewData> {
+    let json = std::fs::read_to_string(path).with_context(|| format!("Unable to read {}", path))?;
+    let output: ReviewOutput = serde_json::from_str(&json)
+        .with_context(|| format!("{} is not a hrevu JSON review", path))?;
+    if output.schema_version > SCHEMA_VERSION {
+        anyhow::bail!(
+            "{} uses output schema version {}, newer than this hrevu supports ({})",
+            path,
+            output.schema_version,
+            SCHEMA_VERSION
+        );
+    }
+    Ok(output.into())
+}
+
+/// All reviews in the history, newest first. Unreadable files are skipped.
+pub fn list_reviews() -> Result<Vec<ReviewData>> {
+    let dir = reviews_dir();
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut reviews = Vec::new();
+    for entry in std::fs::read_dir(&dir).with_context(|| format!("Unable to read {}", dir.display()))? {
+        let path = entry?.path();
+        if path.extension().is_some_and(|ext| ext == "json") {
+            match read_review(&path) {
+                Ok(data) => reviews.push(data),
+                Err(e) => warn!("Skipping {}: {}", path.display(), e),
+            }
+        }
+    }
+
+    reviews.sort_by_key(|data| std::cmp::Reverse(data.created_at));
+    Ok(reviews)
+}
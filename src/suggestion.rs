@@ -0,0 +1,288 @@
+use git2::{DiffOptions, Patch};
+use std::collections::BTreeMap;
+use std::path::Path;
+use tracing::warn;
+
+use crate::models::{Comment, Suggestion};
+
+/// Context lines around each change in the suggestions patch, as in `git diff`
+const PATCH_CONTEXT_LINES: u32 = 3;
+
+/// Marker following a patch line that has no line ending
+const NO_NEWLINE_MARKER: &str = "\\ No newline at end of file\n";
+
+/// Render a unified diff header for a file
+fn file_header(path: &str) -> String {
+    format!("diff --git a/{0} b/{0}\n--- a/{0}\n+++ b/{0}\n", path)
+}
+
+/// The replacement split into lines ending like the commented lines (LF or CRLF).
+/// If the last commented line ends the file without a newline, so does the last replacement line.
+fn replacement_lines(suggestion: &Suggestion) -> Vec<String> {
+    let eol = if suggestion.original.first().is_some_and(|line| line.ends_with("\r\n")) {
+        "\r\n"
+    } else {
+        "\n"
+    };
+    let ends_file = suggestion.original.last().is_some_and(|line| !line.ends_with('\n'));
+
+    let mut lines: Vec<String> = suggestion
+        .replacement
+        .lines()
+        .map(|line| format!("{}{}", line, eol))
+        .collect();
+    if ends_file {
+        if let Some(last) = lines.last_mut() {
+            last.truncate(last.len() - eol.len());
+        }
+    }
+    lines
+}
+
+/// Append a patch line, marking lines that have no line ending
+fn push_patch_line(hunk: &mut String, origin: char, line: &str) {
+    hunk.push(origin);
+    hunk.push_str(line);
+    if !line.ends_with('\n') {
+        hunk.push('\n');
+        hunk.push_str(NO_NEWLINE_MARKER);
+    }
+}
+
+/// Render one suggestion as a zero-context hunk
+fn render_hunk(start: u32, suggestion: &Suggestion) -> String {
+    let replacement = replacement_lines(suggestion);
+
+    // Deletions keep the start line rather than the line before (as `diff -U0` would),
+    // since libgit2 locates zero-context hunks by the new start
+    let mut hunk = format!(
+        "@@ -{},{} +{},{} @@\n",
+        start,
+        suggestion.original.len(),
+        start,
+        replacement.len()
+    );
+    for line in &suggestion.original {
+        push_patch_line(&mut hunk, '-', line);
+    }
+    for line in &replacement {
+        push_patch_line(&mut hunk, '+', line);
+    }
+    hunk
+}
+
+/// Render a single comment's suggestion as a standalone zero-context patch on `path`
+/// (relative to the repository root), for libgit2 to apply. Plain `git apply` needs
+/// `--unidiff-zero` for it.
+pub fn comment_patch(comment: &Comment, path: &str) -> Option<String> {
+    let suggestion = comment.suggestion.as_ref()?;
+    let (start, _) = comment.line_range()?;
+
+    Some(format!("{}{}", file_header(path), render_hunk(start, suggestion)))
+}
+
+/// Render all suggestions as one unified diff with context lines. `source` gives each
+/// commented file's path relative to the repository root and its reviewed content;
+/// files it fails for are left out. So are suggestions overlapping an earlier one in
+/// the same file, or whose lines don't match the content.
+pub fn render_patch(
+    comments: &[Comment],
+    source: impl Fn(&str) -> anyhow::Result<(String, Vec<u8>)>,
+) -> Option<String> {
+    let mut by_file: BTreeMap<&str, Vec<(u32, u32, &Suggestion)>> = BTreeMap::new();
+    for comment in comments {
+        if let (Some(suggestion), Some(path), Some((start, end))) =
+            (&comment.suggestion, &comment.file, comment.line_range())
+        {
+            by_file.entry(path).or_default().push((start, end, suggestion));
+        }
+    }
+
+    let mut patch = String::new();
+    for (file, mut suggestions) in by_file {
+        let (path, old) = match source(file) {
+            Ok(source) => source,
+            Err(e) => {
+                warn!("Leaving suggestions for {} out of the patch: {}", file, e);
+                continue;
+            }
+        };
+        suggestions.sort_by_key(|(start, _, _)| *start);
+
+        let new = apply_suggestions(&path, &old, &suggestions);
+        if new == old {
+            continue;
+        }
+
+        let mut opts = DiffOptions::new();
+        opts.context_lines(PATCH_CONTEXT_LINES);
+        let patch_path = Path::new(&path);
+        match Patch::from_buffers(&old, Some(patch_path), &new, Some(patch_path), Some(&mut opts))
+            .and_then(|mut file_patch| file_patch.to_buf())
+        {
+            Ok(buf) => patch.push_str(&String::from_utf8_lossy(&buf)),
+            Err(e) => warn!("Failed to render suggestions for {}: {}", path, e),
+        }
+    }
+
+    (!patch.is_empty()).then_some(patch)
+}
+
+/// Content with the suggestions (sorted by start line) applied
+fn apply_suggestions(path: &str, content: &[u8], suggestions: &[(u32, u32, &Suggestion)]) -> Vec<u8> {
+    let lines: Vec<&[u8]> = content.split_inclusive(|&b| b == b'\n').collect();
+
+    let mut new = Vec::with_capacity(content.len());
+    let mut next = 0;
+    for (start, end, suggestion) in suggestions {
+        let range = (*start as usize).saturating_sub(1)..*end as usize;
+        if range.start < next {
+            warn!("Skipping overlapping suggestion at {}:{}", path, start);
+            continue;
+        }
+        let matches = lines.get(range.clone()).is_some_and(|commented| {
+            commented
+                .iter()
+                .copied()
+                .eq(suggestion.original.iter().map(|line| line.as_bytes()))
+        });
+        if !matches {
+            warn!("Skipping suggestion at {}:{}: the lines differ from the reviewed content", path, start);
+            continue;
+        }
+
+        new.extend(lines[next..range.start].concat());
+        for line in replacement_lines(suggestion) {
+            new.extend(line.as_bytes());
+        }
+        next = range.end;
+    }
+    new.extend(lines[next..].concat());
+    new
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn patch_for(content: &str, comments: &[Comment]) -> String {
+        render_patch(comments, |path| Ok((path.to_string(), content.as_bytes().to_vec())))
+            .expect("a patch")
+    }
+
+    #[test]
+    fn patch_has_context_lines() {
+        let patch = patch_for("a\nb\nc\n", &[Comment::suggesting("f.txt", 2, 2, &["b\n"], "B")]);
+        assert!(patch.starts_with("diff --git a/f.txt b/f.txt\n"));
+        assert!(patch.contains("@@ -1,3 +1,3 @@\n a\n-b\n+B\n c\n"), "{}", patch);
+    }
+
+    #[test]
+    fn patch_deletes_lines() {
+        let patch = patch_for("a\nb\nc\nd\n", &[Comment::suggesting("f.txt", 2, 3, &["b\n", "c\n"], "")]);
+        assert!(patch.contains("@@ -1,4 +1,2 @@\n a\n-b\n-c\n d\n"), "{}", patch);
+    }
+
+    #[test]
+    fn patch_combines_suggestions_in_one_file() {
+        let content = "1\n2\n3\n4\n5\n6\n7\n8\n9\n10\n";
+        let patch = patch_for(
+            content,
+            &[
+                Comment::suggesting("f.txt", 9, 9, &["9\n"], "nine"),
+                Comment::suggesting("f.txt", 2, 2, &["2\n"], "two\nTWO"),
+            ],
+        );
+        assert_eq!(patch.matches("diff --git").count(), 1);
+        assert!(patch.contains("-2\n+two\n+TWO\n"), "{}", patch);
+        assert!(patch.contains("-9\n+nine\n"), "{}", patch);
+    }
+
+    #[test]
+    fn patch_skips_overlapping_and_stale_suggestions() {
+        let patch = patch_for(
+            "a\nb\nc\n",
+            &[
+                Comment::suggesting("f.txt", 1, 2, &["a\n", "b\n"], "x"),
+                Comment::suggesting("f.txt", 2, 2, &["b\n"], "y"),
+                Comment::suggesting("f.txt", 3, 3, &["changed\n"], "z"),
+            ],
+        );
+        assert!(patch.contains("-a\n-b\n+x\n c\n"), "{}", patch);
+        assert!(!patch.contains("+y") && !patch.contains("+z"), "{}", patch);
+    }
+
+    #[test]
+    fn patch_keeps_crlf_and_trailing_whitespace() {
+        let patch = patch_for("a\r\nb  \r\nc\r\n", &[Comment::suggesting("f.txt", 2, 2, &["b  \r\n"], "B")]);
+        assert!(patch.contains("-b  \r\n+B\r\n"), "{:?}", patch);
+    }
+
+    #[test]
+    fn patch_keeps_missing_newline_at_end_of_file() {
+        let patch = patch_for("a\nb", &[Comment::suggesting("f.txt", 2, 2, &["b"], "B")]);
+        assert!(
+            patch.contains("-b\n\\ No newline at end of file\n+B\n\\ No newline at end of file\n"),
+            "{}",
+            patch
+        );
+    }
+
+    #[test]
+    fn patch_names_files_by_repository_path() {
+        let comments = [Comment::suggesting("/work/repo/sub/f.txt", 1, 1, &["a\n"], "A")];
+        let patch = render_patch(&comments, |_| Ok(("sub/f.txt".to_string(), b"a\n".to_vec())))
+            .expect("a patch");
+        assert!(patch.starts_with("diff --git a/sub/f.txt b/sub/f.txt\n"), "{}", patch);
+    }
+
+    #[test]
+    fn patch_leaves_out_files_without_source() {
+        let comments = [
+            Comment::suggesting("/outside/f.txt", 1, 1, &["a\n"], "A"),
+            Comment::suggesting("g.txt", 1, 1, &["a\n"], "A"),
+        ];
+        let patch = render_patch(&comments, |path| match path {
+            "g.txt" => Ok((path.to_string(), b"a\n".to_vec())),
+            _ => Err(anyhow::anyhow!("not in a git repository")),
+        })
+        .expect("a patch");
+        assert!(!patch.contains("outside"), "{}", patch);
+        assert!(patch.contains("diff --git a/g.txt b/g.txt\n"), "{}", patch);
+    }
+
+    #[test]
+    fn comment_patch_replaces_lines() {
+        let comment = Comment::suggesting("f.txt", 2, 3, &["b\n", "c\n"], "B");
+        let patch = comment_patch(&comment, "f.txt").unwrap();
+        assert_eq!(
+            patch,
+            "diff --git a/f.txt b/f.txt\n--- a/f.txt\n+++ b/f.txt\n@@ -2,2 +2,1 @@\n-b\n-c\n+B\n"
+        );
+    }
+
+    #[test]
+    fn comment_patch_deletes_lines() {
+        let comment = Comment::suggesting("f.txt", 2, 2, &["b\n"], "");
+        let patch = comment_patch(&comment, "f.txt").unwrap();
+        assert!(patch.ends_with("@@ -2,1 +2,0 @@\n-b\n"), "{}", patch);
+    }
+
+    #[test]
+    fn comment_patch_keeps_crlf_and_trailing_whitespace() {
+        let comment = Comment::suggesting("f.txt", 2, 2, &["b \t\r\n"], "B\nC");
+        let patch = comment_patch(&comment, "f.txt").unwrap();
+        assert!(patch.ends_with("@@ -2,1 +2,2 @@\n-b \t\r\n+B\r\n+C\r\n"), "{:?}", patch);
+    }
+
+    #[test]
+    fn comment_patch_marks_missing_newline() {
+        let comment = Comment::suggesting("f.txt", 3, 3, &["c"], "C");
+        let patch = comment_patch(&comment, "f.txt").unwrap();
+        assert!(
+            patch.ends_with("-c\n\\ No newline at end of file\n+C\n\\ No newline at end of file\n"),
+            "{}",
+            patch
+        );
+    }
+}
@@ -14,6 +14,7 @@ const i18n = {
         complete: '完成',
         cancel: '取消',
         noCommentsYet: '暂无评论',
+        noChanges: '没有更改',
         loading: '加载中...',
         failedToLoad: '加载失败',
         commentAdded: '评论已添加',
@@ -22,16 +23,56 @@ const i18n = {
         failedToSave: '保存失败',
         failedToDelete: '删除失败',
         failedToComplete: '完成审查失败',
+        confirmCancel: '确定取消审查吗？已写的评论仍会输出。',
+        reviewCancelled: '审查已取消',
+        cancelled: '已取消',
+        failedToCancel: '取消审查失败',
         reviewComplete: (count) => `审查完成！共 ${count} 条评论`,
         globalCommentLabel: '全局评论',
+        fileComment: '文件评论',
+        addFileComment: '+ 文件评论',
+        binaryFile: '二进制文件',
+        bytes: '字节',
         line: '行',
+        oldSide: '旧',
+        commentOnSelection: '💬 评论所选内容',
+        suggestedChange: '建议修改',
+        renamedFrom: '重命名自',
+        copiedFrom: '复制自',
+        reply: '回复',
+        sinceViewNote: '自上次审查以来的更改（只读）',
+        resolve: '标记已解决',
+        reopen: '重新打开',
+        resolution: {
+            addressed: '已处理',
+            wont_fix: '不予修改',
+        },
+        addReply: '添加回复',
+        replyTo: '回复',
+        replyAdded: '回复已添加',
+        author: {
+            reviewer: '你',
+            agent: '代理',
+        },
+        kind: {
+            blocker: '阻塞问题',
+            change_request: '修改请求',
+            question: '疑问',
+            nit: '小建议',
+            praise: '赞赏',
+        },
         prefix: {
             commit: '提交',
+            commits: '提交范围',
             file: '文件',
         },
         typeLabel: {
             working_tree_diff: '当前更改',
+            staged: '已暂存更改',
+            unstaged: '未暂存更改',
         },
+        branchVs: (base) => `分支对比 ${base}`,
+        withUncommitted: '（含未提交更改）',
     },
     en: {
         files: 'Files',
@@ -47,6 +88,7 @@ const i18n = {
         complete: 'Complete',
         cancel: 'Cancel',
         noCommentsYet: 'No comments yet',
+        noChanges: 'No changes',
         loading: 'Loading...',
         failedToLoad: 'Failed to load data',
         commentAdded: 'Comment added',
@@ -55,16 +97,56 @@ const i18n = {
         failedToSave: 'Failed to save comment',
         failedToDelete: 'Failed to delete comment',
         failedToComplete: 'Failed to complete review',
+        confirmCancel: 'Cancel this review? Comments written so far are still reported.',
+        reviewCancelled: 'Review cancelled',
+        cancelled: 'Cancelled',
+        failedToCancel: 'Failed to cancel review',
         reviewComplete: (count) => `Review complete! ${count} comment${count !== 1 ? 's' : ''}`,
         globalCommentLabel: 'Global comment',
+        fileComment: 'File Comment',
+        addFileComment: '+ File Comment',
+        binaryFile: 'Binary file',
+        bytes: 'bytes',
         line: 'Line',
+        oldSide: 'old',
+        commentOnSelection: '💬 Comment on selection',
+        suggestedChange: 'Suggested change',
+        renamedFrom: 'renamed from',
+        copiedFrom: 'copied from',
+        reply: 'Reply',
+        sinceViewNote: 'Changes since the last review (read-only)',
+        resolve: 'Resolve',
+        reopen: 'Reopen',
+        resolution: {
+            addressed: 'Addressed',
+            wont_fix: "Won't fix",
+        },
+        addReply: 'Add Reply',
+        replyTo: 'Reply to',
+        replyAdded: 'Reply added',
+        author: {
+            reviewer: 'You',
+            agent: 'Agent',
+        },
+        kind: {
+            blocker: 'Blocker',
+            change_request: 'Change request',
+            question: 'Question',
+            nit: 'Nit',
+            praise: 'Praise',
+        },
         prefix: {
             commit: 'Commit',
+            commits: 'Commits',
             file: 'File',
         },
         typeLabel: {
             working_tree_diff: 'Current Changes',
+            staged: 'Staged Changes',
+            unstaged: 'Unstaged Changes',
         },
+        branchVs: (base) => `Branch vs ${base}`,
+        withUncommitted: ' (with uncommitted changes)',
     }
 };
 
@@ -89,16 +171,25 @@ function generateTitle(inputType) {
     const dict = i18n[CURRENT_LANG] || i18n.en;
     const type_ = inputType.type;
 
-    // For working_tree_diff, return label directly
+    // For working_tree_diff, return label for the mode directly
     if (type_ === 'working_tree_diff') {
-        return dict.typeLabel.working_tree_diff;
+        return dict.typeLabel[inputType.mode] || dict.typeLabel.working_tree_diff;
     }
 
-    // For commit_diff and file_content, use prefix + value
+    // For branch_diff, describe the base branch
+    if (type_ === 'branch_diff') {
+        const suffix = inputType.include_uncommitted ? dict.withUncommitted : '';
+        return dict.branchVs(inputType.base) + suffix;
+    }
+
+    // For commit_diff, commit_range and file_content, use prefix + value
     let prefix, value;
     if (type_ === 'commit_diff') {
         prefix = dict.prefix.commit;
         value = inputType.commit;
+    } else if (type_ === 'commit_range') {
+        prefix = dict.prefix.commits;
+        value = `${inputType.from}${inputType.symmetric ? '...' : '..'}${inputType.to}`;
     } else if (type_ === 'file_content') {
         prefix = dict.prefix.file;
         value = inputType.path;
@@ -118,6 +209,14 @@ class ReviewApp {
         this.currentFile = null;
         this.pendingComment = null;
         this.editingComment = null;
+        // ID of the comment being replied to
+        this.replyingTo = null;
+        // Last clicked line, the start of a shift-click range
+        this.lastClickedLine = null;
+        // Set once the review is completed or cancelled
+        this.finished = false;
+        // "full" for the reviewed diff, "since" for changes since the last round
+        this.view = 'full';
 
         this.init();
     }
@@ -186,18 +285,56 @@ class ReviewApp {
 
     bindEvents() {
         // Complete button
-        document.getElementById('complete-btn').addEventListener('click', () => this.completeReview());
+        document.getElementById('complete-btn').addEventListener('click', () => this.openVerdictModal());
+
+        // Full diff / changes since last review
+        document.getElementById('view-switch').addEventListener('click', (e) => {
+            if (e.target.dataset.view) {
+                this.switchView(e.target.dataset.view);
+            }
+        });
+
+        // Cancel button
+        document.getElementById('cancel-review-btn').addEventListener('click', () => this.cancelReview());
+
+        // Warn before leaving with unsubmitted comments
+        window.addEventListener('beforeunload', (e) => {
+            if (!this.finished && this.comments.length > 0) {
+                e.preventDefault();
+                e.returnValue = '';
+            }
+        });
+
+        // Verdict modal
+        document.getElementById('verdict-close').addEventListener('click', () => this.closeVerdictModal());
+        document.getElementById('verdict-cancel').addEventListener('click', () => this.closeVerdictModal());
+        document.getElementById('verdict-submit').addEventListener('click', () => this.completeReview());
+        document.getElementById('verdict-modal').addEventListener('click', (e) => {
+            if (e.target.id === 'verdict-modal') {
+                this.closeVerdictModal();
+            }
+        });
 
         // Comment modal
         document.getElementById('modal-close').addEventListener('click', () => this.closeModal());
         document.getElementById('modal-cancel').addEventListener('click', () => this.closeModal());
         document.getElementById('modal-submit').addEventListener('click', () => this.submitComment());
+        document.getElementById('suggestion-enabled').addEventListener('change', (e) => {
+            document.getElementById('suggestion-text').hidden = !e.target.checked;
+        });
 
         // Global comment
         document.getElementById('add-global-comment-btn').addEventListener('click', () => {
             this.openCommentModal(null, null);
         });
 
+        // File-level comment
+        document.getElementById('add-file-comment-btn').addEventListener('click', () => {
+            if (this.currentFile) {
+                this.openCommentModal(this.currentFile, null);
+            }
+        });
+
         // Comments sidebar
         document.getElementById('comments-close').addEventListener('click', () => {
             document.getElementById('comments-sidebar').classList.remove('active');
@@ -210,6 +347,19 @@ class ReviewApp {
             }
         });
 
+        // Comment on a text selection
+        document.getElementById('diff-view').addEventListener('mouseup', () => {
+            // Let the browser finish updating the selection first
+            setTimeout(() => this.updateSelectionButton(), 0);
+        });
+        document.getElementById('selection-comment-btn').addEventListener('mousedown', (e) => {
+            // Keep the selection alive while clicking the button
+            e.preventDefault();
+        });
+        document.getElementById('selection-comment-btn').addEventListener('click', () => {
+            this.commentOnSelection();
+        });
+
         // Keyboard shortcuts
         document.addEventListener('keydown', (e) => {
             if (e.key === 'Escape') {
@@ -217,19 +367,27 @@ class ReviewApp {
             }
         });
 
-        // Event delegation for edit/delete buttons in diff view
+        // Event delegation for comment action buttons in diff view
         document.getElementById('diff-view').addEventListener('click', (e) => {
             if (e.target.matches('.btn-edit')) {
                 this.editComment(e.target.dataset.id);
+            } else if (e.target.matches('.btn-reply')) {
+                this.openReplyModal(e.target.dataset.id);
+            } else if (e.target.matches('.btn-resolve')) {
+                this.setResolution(e.target.dataset.id, e.target.dataset.resolution);
             } else if (e.target.matches('.btn-delete')) {
                 this.deleteComment(e.target.dataset.id);
             }
         });
 
-        // Event delegation for edit/delete buttons in comments sidebar
+        // Event delegation for comment action buttons in comments sidebar
         document.getElementById('comments-list').addEventListener('click', (e) => {
             if (e.target.matches('.btn-edit')) {
                 this.editComment(e.target.dataset.id);
+            } else if (e.target.matches('.btn-reply')) {
+                this.openReplyModal(e.target.dataset.id);
+            } else if (e.target.matches('.btn-resolve')) {
+                this.setResolution(e.target.dataset.id, e.target.dataset.resolution);
             } else if (e.target.matches('.btn-delete')) {
                 this.deleteComment(e.target.dataset.id);
             }
@@ -244,6 +402,7 @@ class ReviewApp {
             document.getElementById('review-title').textContent = generateTitle(this.data.input_type);
             this.files = this.data.files;
             this.comments = this.data.comments;
+            document.getElementById('view-switch').hidden = !this.data.interdiff;
 
             this.renderFileList();
             this.renderComments();
@@ -258,6 +417,27 @@ class ReviewApp {
         }
     }
 
+    // Show the full diff or the changes since the last review. Comments belong to the
+    // full diff, so the changes view is read-only.
+    switchView(view) {
+        this.view = view;
+        this.files = view === 'since' ? this.data.interdiff : this.data.files;
+
+        document.querySelectorAll('#view-switch [data-view]').forEach(btn => {
+            btn.classList.toggle('active', btn.dataset.view === view);
+        });
+
+        this.currentFile = null;
+        this.renderFileList();
+        if (this.files.length > 0) {
+            this.selectFile(this.files[0].path);
+        } else {
+            document.getElementById('current-file-name').textContent = t('selectFile');
+            document.getElementById('add-file-comment-btn').hidden = true;
+            document.getElementById('diff-view').innerHTML = `<div class="empty-state">${t('noChanges')}</div>`;
+        }
+    }
+
     showError(message) {
         const toast = document.createElement('div');
         toast.className = 'toast toast-error';
@@ -294,8 +474,9 @@ class ReviewApp {
 
         fileList.innerHTML = this.files.map(file => {
             const commentCount = commentsByFile[file.path] || 0;
+            const title = file.old_path ? `${file.old_path} → ${file.path}` : file.path;
             return `
-                <div class="file-item" data-path="${this.escapeHtml(file.path)}">
+                <div class="file-item" data-path="${this.escapeHtml(file.path)}" title="${this.escapeHtml(title)}">
                     <span class="file-status ${file.status}"></span>
                     <span class="file-path">${this.escapeHtml(file.path)}</span>
                     ${commentCount > 0 ? `<span class="file-comment-count">${commentCount}</span>` : ''}
@@ -321,6 +502,7 @@ class ReviewApp {
 
         // Update header
         document.getElementById('current-file-name').textContent = filePath;
+        document.getElementById('add-file-comment-btn').hidden = this.view === 'since';
 
         // Render diff
         this.renderDiff(filePath);
@@ -335,14 +517,22 @@ class ReviewApp {
             return;
         }
 
-        // Get comments for this file grouped by line
+        const readOnly = this.view === 'since';
+        const comments = readOnly ? [] : this.comments;
+
+        // File-level comments have a file but no line
+        const fileComments = comments.filter(c => c.file === filePath && !c.line);
+
+        // Get comments for this file grouped by side and line, e.g. "old:12"
         const commentsByLine = {};
-        for (const comment of this.comments) {
+        for (const comment of comments) {
             if (comment.file === filePath && comment.line) {
-                if (!commentsByLine[comment.line]) {
-                    commentsByLine[comment.line] = [];
+                // Range comments are shown under their last line
+                const key = `${comment.side || 'new'}:${comment.end_line || comment.line}`;
+                if (!commentsByLine[key]) {
+                    commentsByLine[key] = [];
                 }
-                commentsByLine[comment.line].push(comment);
+                commentsByLine[key].push(comment);
             }
         }
 
@@ -350,64 +540,289 @@ class ReviewApp {
             <div class="file-diff-header">
                 <span class="file-status ${file.status}"></span>
                 <h3>${this.escapeHtml(filePath)}</h3>
+                ${this.renderRenameInfo(file)}
             </div>`;
 
-        for (const line of file.lines) {
-            const lineClass = line.type ? line.type : 'context';
-            const hasComments = commentsByLine[line.number] && commentsByLine[line.number].length > 0;
-
-            html += `
-                <div class="diff-line ${lineClass}"
-                     data-file="${this.escapeHtml(filePath)}"
-                     data-line="${line.number}">
-                    <span class="diff-line-number">${line.number > 0 ? line.number : ''}</span>
-                    <span class="diff-line-content"><code>${this.highlightCode(line.content, filePath)}</code></span>
-                    ${hasComments ? `<span class="comment-marker">${commentsByLine[line.number].length}</span>` : ''}
-                </div>
-            `;
+        if (readOnly) {
+            html += `<div class="view-note">${t('sinceViewNote')}</div>`;
+        }
+
+        if (fileComments.length > 0) {
+            html += '<div class="inline-comments file-comments">';
+            for (const comment of fileComments) {
+                html += this.renderInlineComment(comment);
+            }
+            html += '</div>';
+        }
+
+        if (file.binary) {
+            html += `<div class="binary-file">${t('binaryFile')} · ${this.formatSizes(file)}</div>`;
+        }
+
+        // File view has no old side, so it only gets one line number column
+        const showOldNumbers = file.status !== 'view';
+
+        for (const hunk of file.hunks) {
+            if (hunk.header) {
+                html += `<div class="hunk-header">${this.escapeHtml(hunk.header)}</div>`;
+            }
+
+            for (const line of hunk.lines) {
+                const lineClass = line.type ? line.type : 'context';
+                // Removed lines only exist on the old side; everything else is anchored to the new side
+                const side = line.new_number != null ? 'new' : 'old';
+                const anchor = side === 'new' ? line.new_number : line.old_number;
+                const lineComments = [
+                    ...(line.new_number != null ? commentsByLine[`new:${line.new_number}`] || [] : []),
+                    ...(line.old_number != null ? commentsByLine[`old:${line.old_number}`] || [] : []),
+                ];
+                const hasComments = lineComments.length > 0;
+
+                html += `
+                    <div class="diff-line ${lineClass}"
+                         data-file="${this.escapeHtml(filePath)}"
+                         data-line="${anchor}"
+                         data-side="${side}">
+                        ${showOldNumbers ? `<span class="diff-line-number">${line.old_number != null ? line.old_number : ''}</span>` : ''}
+                        <span class="diff-line-number">${line.new_number != null ? line.new_number : ''}</span>
+                        <span class="diff-line-content"><code>${this.highlightCode(line.content, filePath)}</code></span>
+                        ${hasComments ? `<span class="comment-marker">${lineComments.length}</span>` : ''}
+                    </div>
+                `;
 
-            // Render inline comments
-            if (hasComments) {
-                html += '<div class="inline-comments">';
-                for (const comment of commentsByLine[line.number]) {
-                    html += this.renderInlineComment(comment);
+                // Render inline comments
+                if (hasComments) {
+                    html += '<div class="inline-comments">';
+                    for (const comment of lineComments) {
+                        html += this.renderInlineComment(comment);
+                    }
+                    html += '</div>';
                 }
-                html += '</div>';
             }
         }
 
         html += '</div>';
         diffView.innerHTML = html;
 
-        // Add click handlers to lines
+        if (readOnly) {
+            return;
+        }
+
+        // Add click handlers to lines; shift-click extends from the last clicked line
         diffView.querySelectorAll('.diff-line').forEach(lineEl => {
-            lineEl.addEventListener('click', () => {
+            lineEl.addEventListener('click', (e) => {
+                // Selecting text is handled by the selection button instead
+                if (!window.getSelection().isCollapsed) {
+                    return;
+                }
+
                 const file = lineEl.dataset.file;
+                const side = lineEl.dataset.side;
                 const lineNum = parseInt(lineEl.dataset.line);
-                if (lineNum > 0) {
-                    this.openCommentModal(file, lineNum);
+                if (!(lineNum > 0)) {
+                    return;
+                }
+
+                const last = this.lastClickedLine;
+                if (e.shiftKey && last && last.file === file && last.side === side && last.line !== lineNum) {
+                    const start = Math.min(last.line, lineNum);
+                    const end = Math.max(last.line, lineNum);
+                    this.lastClickedLine = null;
+                    this.highlightRange(side, start, end);
+                    this.openCommentModal(file, start, side, end);
+                } else {
+                    this.lastClickedLine = { file, side, line: lineNum };
+                    this.highlightRange(side, lineNum, lineNum);
+                    this.openCommentModal(file, lineNum, side);
                 }
             });
         });
     }
 
+    // Resolve the current text selection to a line/column anchor, or null
+    selectionAnchor() {
+        const selection = window.getSelection();
+        if (!selection || selection.isCollapsed || selection.rangeCount === 0) {
+            return null;
+        }
+
+        const range = selection.getRangeAt(0);
+        const startCode = this.codeElementOf(range.startContainer);
+        const endCode = this.codeElementOf(range.endContainer);
+        if (!startCode || !endCode) {
+            return null;
+        }
+
+        const startLine = startCode.closest('.diff-line');
+        const endLine = endCode.closest('.diff-line');
+        if (startLine.dataset.file !== endLine.dataset.file || startLine.dataset.side !== endLine.dataset.side) {
+            return null;
+        }
+
+        return {
+            file: startLine.dataset.file,
+            side: startLine.dataset.side,
+            line: parseInt(startLine.dataset.line),
+            endLine: parseInt(endLine.dataset.line),
+            startColumn: this.columnOffset(startCode, range.startContainer, range.startOffset),
+            endColumn: this.columnOffset(endCode, range.endContainer, range.endOffset),
+            rect: range.getBoundingClientRect(),
+        };
+    }
+
+    codeElementOf(node) {
+        const el = node.nodeType === Node.ELEMENT_NODE ? node : node.parentElement;
+        return el ? el.closest('.diff-line-content code') : null;
+    }
+
+    // Character (code point) offset of a DOM position within a line's code element
+    columnOffset(codeEl, container, offset) {
+        const range = document.createRange();
+        range.selectNodeContents(codeEl);
+        range.setEnd(container, offset);
+        return Array.from(range.toString()).length;
+    }
+
+    updateSelectionButton() {
+        const btn = document.getElementById('selection-comment-btn');
+        const anchor = this.view === 'since' ? null : this.selectionAnchor();
+        if (!anchor) {
+            btn.classList.remove('active');
+            return;
+        }
+
+        btn.style.top = `${anchor.rect.bottom + window.scrollY + 6}px`;
+        btn.style.left = `${anchor.rect.left + window.scrollX}px`;
+        btn.classList.add('active');
+    }
+
+    commentOnSelection() {
+        const anchor = this.selectionAnchor();
+        document.getElementById('selection-comment-btn').classList.remove('active');
+        if (!anchor) {
+            return;
+        }
+
+        this.openCommentModal(anchor.file, anchor.line, anchor.side, anchor.endLine);
+        this.pendingComment.startColumn = anchor.startColumn;
+        this.pendingComment.endColumn = anchor.endColumn;
+        window.getSelection().removeAllRanges();
+    }
+
+    highlightRange(side, start, end) {
+        document.querySelectorAll('.diff-line').forEach(lineEl => {
+            const lineNum = parseInt(lineEl.dataset.line);
+            const selected = lineEl.dataset.side === side && lineNum >= start && lineNum <= end;
+            lineEl.classList.toggle('selected', selected);
+        });
+    }
+
+    renderRenameInfo(file) {
+        if (!file.old_path) {
+            return '';
+        }
+        const label = file.status === 'copied' ? t('copiedFrom') : t('renamedFrom');
+        const similarity = file.similarity != null ? ` (${file.similarity}%)` : '';
+        return `<span class="file-rename-info">${label} ${this.escapeHtml(file.old_path)}${similarity}</span>`;
+    }
+
+    formatSizes(file) {
+        const fmt = (size) => size != null ? `${size} ${t('bytes')}` : '—';
+        if (file.old_size != null && file.new_size != null) {
+            return `${fmt(file.old_size)} → ${fmt(file.new_size)}`;
+        }
+        return fmt(file.new_size != null ? file.new_size : file.old_size);
+    }
+
     renderInlineComment(comment) {
         const time = new Date(comment.created_at).toLocaleTimeString();
         return `
-            <div class="inline-comment" data-id="${comment.id}">
+            <div class="inline-comment ${this.resolutionClass(comment)}" data-id="${comment.id}">
                 <div class="inline-comment-header">
                     <span class="inline-comment-author">You</span>
+                    ${this.renderKindBadge(comment)}
+                    ${this.renderResolutionBadge(comment)}
                     <span class="inline-comment-time">${time}</span>
                 </div>
+                ${this.renderSelectedText(comment)}
                 <div class="inline-comment-text">${this.escapeHtml(comment.text)}</div>
+                ${this.renderSuggestion(comment)}
                 <div class="inline-comment-actions">
                     <button class="btn-edit" data-id="${comment.id}">${t('edit')}</button>
+                    <button class="btn-reply" data-id="${comment.id}">${t('reply')}</button>
+                    ${this.renderResolveButton(comment)}
                     <button class="btn-delete" data-id="${comment.id}">${t('delete')}</button>
                 </div>
+                ${this.renderReplies(comment)}
             </div>
         `;
     }
 
+    resolutionClass(comment) {
+        return comment.resolution && comment.resolution !== 'open' ? 'resolved' : '';
+    }
+
+    renderResolutionBadge(comment) {
+        if (!this.resolutionClass(comment)) {
+            return '';
+        }
+        const dict = i18n[CURRENT_LANG] || i18n.en;
+        return `<span class="comment-resolution">${dict.resolution[comment.resolution]}</span>`;
+    }
+
+    renderResolveButton(comment) {
+        const [label, resolution] = this.resolutionClass(comment)
+            ? [t('reopen'), 'open']
+            : [t('resolve'), 'addressed'];
+        return `<button class="btn-resolve" data-id="${comment.id}" data-resolution="${resolution}">${label}</button>`;
+    }
+
+    renderReplies(comment) {
+        if (!comment.replies || comment.replies.length === 0) {
+            return '';
+        }
+        const dict = i18n[CURRENT_LANG] || i18n.en;
+        const replies = comment.replies.map(reply => `
+            <div class="comment-reply ${reply.author || 'reviewer'}" data-id="${reply.id}">
+                <div class="inline-comment-header">
+                    <span class="inline-comment-author">${dict.author[reply.author || 'reviewer']}</span>
+                    <span class="inline-comment-time">${new Date(reply.created_at).toLocaleTimeString()}</span>
+                </div>
+                <div class="inline-comment-text">${this.escapeHtml(reply.text)}</div>
+                <div class="inline-comment-actions">
+                    <button class="btn-edit" data-id="${reply.id}">${t('edit')}</button>
+                    <button class="btn-delete" data-id="${reply.id}">${t('delete')}</button>
+                </div>
+            </div>
+        `).join('');
+        return `<div class="comment-replies">${replies}</div>`;
+    }
+
+    renderKindBadge(comment) {
+        const kind = comment.kind || 'change_request';
+        const dict = i18n[CURRENT_LANG] || i18n.en;
+        return `<span class="comment-kind ${kind}">${dict.kind[kind] || kind}</span>`;
+    }
+
+    renderSelectedText(comment) {
+        if (!comment.selected_text) {
+            return '';
+        }
+        return `<blockquote class="comment-selection"><code>${this.escapeHtml(comment.selected_text)}</code></blockquote>`;
+    }
+
+    renderSuggestion(comment) {
+        if (!comment.suggestion) {
+            return '';
+        }
+        const removed = comment.suggestion.original
+            .map(line => `<div class="removed">- ${this.escapeHtml(line.replace(/\r?\n$/, ''))}</div>`).join('');
+        const replacement = comment.suggestion.replacement.replace(/\n$/, '');
+        const added = (replacement === '' ? [] : replacement.split('\n'))
+            .map(line => `<div class="added">+ ${this.escapeHtml(line)}</div>`).join('');
+        return `<div class="suggestion-diff" title="${t('suggestedChange')}">${removed}${added}</div>`;
+    }
+
     renderComments() {
         const commentsList = document.getElementById('comments-list');
 
@@ -418,40 +833,50 @@ class ReviewApp {
 
         commentsList.innerHTML = this.comments.map(comment => {
             const time = new Date(comment.created_at).toLocaleString();
-            const location = comment.file
-                ? `${comment.file}${comment.line ? ':' + comment.line : ''}`
-                : t('globalCommentLabel');
+            const location = this.formatLocation(comment.file, comment.line, comment.side, comment.end_line);
 
             return `
-                <div class="comment-card" data-id="${comment.id}">
+                <div class="comment-card ${this.resolutionClass(comment)}" data-id="${comment.id}">
                     <div class="comment-card-header">
                         <span class="comment-card-location">${this.escapeHtml(location)}</span>
+                        ${this.renderKindBadge(comment)}
+                        ${this.renderResolutionBadge(comment)}
                         <span class="comment-card-time">${time}</span>
                     </div>
+                    ${this.renderSelectedText(comment)}
                     <div class="comment-card-text">${this.escapeHtml(comment.text)}</div>
                     <div class="comment-card-actions">
                         <button class="btn-edit" data-id="${comment.id}">${t('edit')}</button>
+                        <button class="btn-reply" data-id="${comment.id}">${t('reply')}</button>
+                        ${this.renderResolveButton(comment)}
                         <button class="btn-delete" data-id="${comment.id}">${t('delete')}</button>
                     </div>
+                    ${this.renderReplies(comment)}
                 </div>
             `;
         }).join('');
     }
 
-    openCommentModal(file, line) {
-        this.pendingComment = { file, line };
-        this.editingComment = null;
-
-        const info = document.getElementById('comment-info');
+    formatLocation(file, line, side, endLine = null) {
         if (file && line) {
-            info.textContent = `${file}:${line}`;
+            const lines = endLine && endLine !== line ? `${line}-${endLine}` : `${line}`;
+            return side === 'old' ? `${file}:${lines} (${t('oldSide')})` : `${file}:${lines}`;
         } else if (file) {
-            info.textContent = file;
-        } else {
-            info.textContent = t('globalCommentLabel');
+            return file;
         }
+        return t('globalCommentLabel');
+    }
+
+    openCommentModal(file, line, side = null, endLine = null) {
+        this.pendingComment = { file, line, side, endLine };
+        this.editingComment = null;
+
+        document.getElementById('comment-info').textContent = this.formatLocation(file, line, side, endLine);
 
         document.getElementById('comment-text').value = '';
+        document.getElementById('comment-kind').value = 'change_request';
+        document.getElementById('comment-kind').hidden = false;
+        this.setupSuggestion(null);
         document.getElementById('modal-submit').textContent = t('addComment');
         document.getElementById('comment-modal').classList.add('active');
         document.getElementById('comment-text').focus();
@@ -459,27 +884,111 @@ class ReviewApp {
 
     openEditModal(comment) {
         this.editingComment = comment;
-        this.pendingComment = { file: comment.file, line: comment.line };
+        this.pendingComment = { file: comment.file, line: comment.line, side: comment.side, endLine: comment.end_line };
 
-        const info = document.getElementById('comment-info');
-        if (comment.file && comment.line) {
-            info.textContent = `${comment.file}:${comment.line}`;
-        } else if (comment.file) {
-            info.textContent = comment.file;
-        } else {
-            info.textContent = t('globalCommentLabel');
-        }
+        document.getElementById('comment-info').textContent =
+            this.formatLocation(comment.file, comment.line, comment.side, comment.end_line);
 
         document.getElementById('comment-text').value = comment.text;
+        document.getElementById('comment-kind').value = comment.kind || 'change_request';
+        document.getElementById('comment-kind').hidden = !!comment.parent_id;
+        this.setupSuggestion(comment.suggestion);
         document.getElementById('modal-submit').textContent = t('updateComment');
         document.getElementById('comment-modal').classList.add('active');
         document.getElementById('comment-text').focus();
     }
 
+    openReplyModal(id) {
+        const thread = this.findComment(id);
+        if (!thread) {
+            return;
+        }
+        this.replyingTo = id;
+        this.pendingComment = null;
+        this.editingComment = null;
+
+        document.getElementById('comment-info').textContent =
+            `${t('replyTo')} ${this.formatLocation(thread.file, thread.line, thread.side, thread.end_line)}`;
+
+        document.getElementById('comment-text').value = '';
+        document.getElementById('comment-kind').hidden = true;
+        this.setupSuggestion(null);
+        document.getElementById('modal-submit').textContent = t('addReply');
+        document.getElementById('comment-modal').classList.add('active');
+        document.getElementById('comment-text').focus();
+    }
+
+    // Find a comment or a reply by ID
+    findComment(id) {
+        for (const comment of this.comments) {
+            if (comment.id === id) {
+                return comment;
+            }
+            const reply = (comment.replies || []).find(r => r.id === id);
+            if (reply) {
+                return reply;
+            }
+        }
+        return null;
+    }
+
+    // Replace a comment or reply with its updated version, keeping the thread's replies
+    replaceComment(updated) {
+        const thread = updated.parent_id ? this.findComment(updated.parent_id) : null;
+        const list = thread ? thread.replies : this.comments;
+        const idx = list.findIndex(c => c.id === updated.id);
+        if (idx !== -1) {
+            list[idx] = updated;
+        }
+    }
+
+    // Lines on the new side of the pending comment, or null if a suggestion doesn't fit
+    pendingOriginalLines() {
+        const { file, line, side, endLine } = this.pendingComment || {};
+        if (!file || !line || side === 'old') {
+            return null;
+        }
+        const fileData = this.files.find(f => f.path === file);
+        if (!fileData) {
+            return null;
+        }
+
+        const end = endLine || line;
+        const lines = fileData.hunks
+            .flatMap(hunk => hunk.lines)
+            .filter(l => l.new_number != null && l.new_number >= line && l.new_number <= end)
+            .map(l => l.content);
+        return lines.length > 0 ? lines : null;
+    }
+
+    setupSuggestion(suggestion) {
+        const original = this.pendingOriginalLines();
+        const section = document.getElementById('suggestion-section');
+        const enabled = document.getElementById('suggestion-enabled');
+        const textarea = document.getElementById('suggestion-text');
+
+        section.hidden = !original;
+        enabled.checked = !!suggestion;
+        textarea.hidden = !suggestion;
+        textarea.value = suggestion ? suggestion.replacement : (original || []).join('\n');
+    }
+
+    // Replacement code from the modal, '' to clear, or undefined if not applicable
+    pendingSuggestion() {
+        if (document.getElementById('suggestion-section').hidden) {
+            return undefined;
+        }
+        return document.getElementById('suggestion-enabled').checked
+            ? document.getElementById('suggestion-text').value
+            : '';
+    }
+
     closeModal() {
         document.getElementById('comment-modal').classList.remove('active');
+        document.querySelectorAll('.diff-line.selected').forEach(el => el.classList.remove('selected'));
         this.pendingComment = null;
         this.editingComment = null;
+        this.replyingTo = null;
     }
 
     async submitComment() {
@@ -488,38 +997,56 @@ class ReviewApp {
 
         try {
             let response;
-            if (this.editingComment) {
+            if (this.replyingTo) {
+                response = await fetch(`/api/comments/${this.replyingTo}/replies`, {
+                    method: 'POST',
+                    headers: { 'Content-Type': 'application/json' },
+                    body: JSON.stringify({ text })
+                });
+
+                if (response.ok) {
+                    const reply = await response.json();
+                    const thread = this.findComment(reply.parent_id);
+                    thread.replies = [...(thread.replies || []), reply];
+                    this.showSuccess(t('replyAdded'));
+                } else {
+                    this.showError(await response.text());
+                    return;
+                }
+            } else if (this.editingComment) {
                 // Update existing comment
                 response = await fetch(`/api/comments/${this.editingComment.id}`, {
                     method: 'PUT',
                     headers: { 'Content-Type': 'application/json' },
-                    body: JSON.stringify({ text })
+                    body: JSON.stringify({
+                        text,
+                        kind: document.getElementById('comment-kind').value,
+                        suggestion: this.pendingSuggestion()
+                    })
                 });
 
                 if (response.ok) {
-                    const updated = await response.json();
-                    const idx = this.comments.findIndex(c => c.id === this.editingComment.id);
-                    if (idx !== -1) {
-                        this.comments[idx] = updated;
-                    }
+                    this.replaceComment(await response.json());
                     this.showSuccess(t('commentUpdated'));
+                } else {
+                    this.showError(await response.text());
+                    return;
                 }
             } else {
                 // Add new comment
                 response = await fetch('/api/comments', {
                     method: 'POST',
                     headers: { 'Content-Type': 'application/json' },
-                    body: JSON.stringify({
-                        file: this.pendingComment.file,
-                        line: this.pendingComment.line,
-                        text: text
-                    })
+                    body: JSON.stringify(this.buildCommentAnchor(text))
                 });
 
                 if (response.ok) {
                     const comment = await response.json();
                     this.comments.push(comment);
                     this.showSuccess(t('commentAdded'));
+                } else {
+                    this.showError(await response.text());
+                    return;
                 }
             }
 
@@ -535,8 +1062,44 @@ class ReviewApp {
         }
     }
 
+    buildCommentAnchor(text) {
+        const { file, line, side, endLine, startColumn, endColumn } = this.pendingComment;
+        const columns = startColumn != null ? { start_column: startColumn, end_column: endColumn } : {};
+        const replacement = this.pendingSuggestion();
+        const wantsSuggestion = replacement !== undefined && document.getElementById('suggestion-enabled').checked;
+        const suggestion = wantsSuggestion ? { suggestion: replacement } : {};
+        const kind = document.getElementById('comment-kind').value;
+        if (endLine && endLine !== line) {
+            return { file, start_line: line, end_line: endLine, side, kind, text, ...columns, ...suggestion };
+        }
+        return { file, line, side, kind, text, ...columns, ...suggestion };
+    }
+
+    async setResolution(id, resolution) {
+        try {
+            const response = await fetch(`/api/comments/${id}`, {
+                method: 'PUT',
+                headers: { 'Content-Type': 'application/json' },
+                body: JSON.stringify({ resolution })
+            });
+
+            if (response.ok) {
+                this.replaceComment(await response.json());
+                this.renderComments();
+                if (this.currentFile) {
+                    this.renderDiff(this.currentFile);
+                }
+            } else {
+                this.showError(await response.text());
+            }
+        } catch (error) {
+            console.error('Failed to update comment:', error);
+            this.showError(t('failedToSave'));
+        }
+    }
+
     editComment(id) {
-        const comment = this.comments.find(c => c.id === id);
+        const comment = this.findComment(id);
         if (comment) {
             this.openEditModal(comment);
         }
@@ -550,6 +1113,9 @@ class ReviewApp {
 
             if (response.ok) {
                 this.comments = this.comments.filter(c => c.id !== id);
+                for (const comment of this.comments) {
+                    comment.replies = (comment.replies || []).filter(r => r.id !== id);
+                }
                 this.renderFileList();
                 this.renderComments();
                 if (this.currentFile) {
@@ -563,20 +1129,31 @@ class ReviewApp {
         }
     }
 
+    openVerdictModal() {
+        document.getElementById('verdict-modal').classList.add('active');
+    }
+
+    closeVerdictModal() {
+        document.getElementById('verdict-modal').classList.remove('active');
+    }
+
     async completeReview() {
+        const verdict = document.querySelector('input[name="verdict"]:checked').value;
         try {
             const response = await fetch('/api/complete', {
-                method: 'POST'
+                method: 'POST',
+                headers: { 'Content-Type': 'application/json' },
+                body: JSON.stringify({ verdict })
             });
 
             if (response.ok) {
                 const result = await response.json();
+                this.closeVerdictModal();
                 this.showSuccess(t('reviewComplete', result.comment_count), true);
 
                 // Update button state
-                const btn = document.getElementById('complete-btn');
-                btn.textContent = `✓ ${t('complete')}`;
-                btn.disabled = true;
+                this.markFinished();
+                document.getElementById('complete-btn').textContent = `✓ ${t('complete')}`;
             }
         } catch (error) {
             console.error('Failed to complete review:', error);
@@ -584,6 +1161,31 @@ class ReviewApp {
         }
     }
 
+    async cancelReview() {
+        if (!confirm(t('confirmCancel'))) {
+            return;
+        }
+        try {
+            const response = await fetch('/api/cancel', { method: 'POST' });
+
+            if (response.ok) {
+                this.showSuccess(t('reviewCancelled'), true);
+                this.markFinished();
+                document.getElementById('cancel-review-btn').textContent = t('cancelled');
+            }
+        } catch (error) {
+            console.error('Failed to cancel review:', error);
+            this.showError(t('failedToCancel'));
+        }
+    }
+
+    // The CLI has exited with the result, so further changes would be lost
+    markFinished() {
+        this.finished = true;
+        document.getElementById('complete-btn').disabled = true;
+        document.getElementById('cancel-review-btn').disabled = true;
+    }
+
     escapeHtml(text) {
         const div = document.createElement('div');
         div.textContent = text;